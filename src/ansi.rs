@@ -1,74 +1,282 @@
-pub const REPLACE_MAP: [(&str, &str); 20] = [
-    ("_X", X), ("_BLD", BLD), ("_ITL", ITL), ("_UND", UND),
-    (".BLK", BLK), (".RED", RED), (".GRN", GRN), (".YLW", YLW), (".BLU", BLU), (".MGT", MGT), (".CYN", CYN), (".WHT", WHT),
-    ("#BLK", BBLK), ("#RED", BRED), ("#GRN", BGRN), ("#YLW", BYLW), ("#BLU", BBLU), ("#MGT", BMGT), ("#CYN", BCYN), ("#WHT", BWHT)
-];
-
-// Denoted by a _
-pub const X:       &str = "\x1b[0m";  // Clear code
-
-pub const BLK: &str = "\x1b[30m"; // Black
-pub const RED: &str = "\x1b[31m"; // Red
-pub const GRN: &str = "\x1b[32m"; // Green
-pub const YLW: &str = "\x1b[33m"; // Yellow
-pub const BLU: &str = "\x1b[34m"; // Blue
-pub const MGT: &str = "\x1b[35m"; // Magenta
-pub const CYN: &str = "\x1b[36m"; // Cyan
-pub const WHT: &str = "\x1b[37m"; // White
+use std::fmt;
+
+mod links;
+mod sequences;
+mod style;
+pub use links::{file_url, hyperlink};
+pub use sequences::{strip_ansi, visible_width};
+pub use style::{Attribute, Color, Colorize, ParseStyleError, Style, Styled};
+pub use util_markup::codes::*;
+pub use util_markup::palette;
+use util_markup::{tags, tokens};
+use tokens::{Token, Tokens};
+
+/// Scoped tag markup, which can be nested and goes back to the style around
+/// it when closed, unlike `_X` which resets everything.
+/// 
+/// A tag is a list of words in square brackets, `[bold red on #202020]`,
+/// and is closed by the same words after a slash, `[/bold red on #202020]`,
+/// or by `[/]` for the last tag opened. Closing a tag closes any tags
+/// opened inside of it too, and tags still open at the end are closed.
+/// 
+/// | Word | Meaning |
+/// | ---- | ------- |
+/// | `bold`, `dim`, `italic`, `underline`, `blink`, `reverse`, `hidden`, `strikethrough`, `double_underline`, `overline` | Style |
+/// | `black`, `red`, `green`, `yellow`, `blue`, `magenta`, `cyan`, `white` | Colour of the terminal |
+/// | `bright_black` to `bright_white` | Bright colour of the terminal |
+/// | `default` | Default colour of the terminal |
+/// | `color(0)` to `color(255)` | Colour of the [256 colour palette](palette) |
+/// | `#ff8800`, `#f80` | Hex RGB colour |
+/// | `orange`, `slategray`, ... | One of the other [`palette::CSS_COLORS`] |
+/// | `on` | Makes the next colour the background |
+/// 
+/// A hyperlink is a tag of its own, `[link=https://example.com]text[/link]`,
+/// written like [`hyperlink`], so as `text (url)` where hyperlinks aren't
+/// supported.
+/// 
+/// The `_`/`.`/`#` codes of [`replace_cc`] work alongside tags. Text in
+/// square brackets which isn't a valid tag is left as it is, and a tag can
+/// be escaped with a backslash like the other codes, `\[red]` prints
+/// `[red]`. The [`crate::colprint!`] family of macros understands tags too,
+/// so text like `[default]` or `[bold]` in their format strings needs
+/// escaping to be printed as is. [`replace_cc`] doesn't read tags.
+pub mod markup;
+
+/// Parses text with ansi escape sequences in it, such as the output of
+/// another program or of [`crate::colprint!`], into [`spans::Span`]s of
+/// text in a single [`Style`] and writes them back out.
+/// 
+/// ---
+/// ## Examples
+/// Cutting coloured text down to its first 5 characters:
+/// ```
+/// use utils::ansi::spans;
+/// 
+/// let mut output = spans::parse("\x1b[32mHello,\x1b[0m world!");
+/// output[0].text.truncate(5);
+/// output.truncate(1);
+/// assert_eq!(spans::render(&output), "\x1b[32mHello\x1b[0m");
+/// ```
+pub mod spans;
+
+/// Converts coloured text, or the markup of [`crate::colprint!`], into
+/// HTML, such as for keeping the output of a CI job as an artifact. See
+/// [`html::Html`] for the options.
+pub mod html;
+
+/// Decides whether escape codes are written at all, honoring `NO_COLOR`,
+/// `CLICOLOR`, `CLICOLOR_FORCE`, `TERM=dumb` and whether the output is a
+/// terminal.
+/// 
+/// The printing macros of the [`crate::colprint!`] family, [`f_color::rgb`],
+/// [`b_color::rgb`], [`crate::set_frgb!`] and [`crate::set_brgb!`] all
+/// leave out their escape codes when colour is disabled. Anything not
+/// written to the stderr follows the policy of the stdout.
+/// [`crate::colformat!`] and [`crate::colwrite!`] can't know where their
+/// output goes, so like [`replace_cc`] they always write the codes.
+/// 
+/// ---
+/// # Examples
+/// ```
+/// use utils::{colformat, ansi::policy::{self, ColorMode, Stream}};
+/// 
+/// policy::set_mode(ColorMode::Never);
+/// assert!(!policy::enabled(Stream::Stdout));
+/// assert_eq!(policy::paint("\x1b[32m", Stream::Stdout), "");
+/// 
+/// policy::set_mode(ColorMode::Always);
+/// assert_eq!(policy::paint("\x1b[32m", Stream::Stdout), "\x1b[32m");
+/// 
+/// let line = match policy::enabled(Stream::Stderr) {
+///     true => colformat!(".GRNok_X"),
+///     false => String::from("ok"),
+/// };
+/// assert_eq!(line, "\x1b[32mok\x1b[0m");
+/// ```
+pub mod policy;
+
+/// Operating system commands for the terminal around the program, setting
+/// the window title, copying to the clipboard and sending desktop
+/// notifications. Each is a builder which is written with `write_to` or
+/// with its `Display`.
+/// 
+/// Like [`replace_cc`] these are written regardless of [`policy`], check
+/// [`policy::enabled`] first if the output might not be a terminal.
+/// 
+/// ---
+/// ## Examples
+/// ```no_run
+/// use std::io::{self, Write};
+/// use utils::ansi::{osc::{Notification, Title}, policy::{self, Stream}};
+/// 
+/// let mut stdout = io::stdout();
+/// if policy::enabled(Stream::Stdout) {
+///     Title::new("deploy: 3/4").write_to(&mut stdout)?;
+///     Notification::new("Deploy finished").title("deploy").write_to(&mut stdout)?;
+///     stdout.flush()?;
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+pub mod osc;
 
 pub mod f_color { // Denoted by a .
 
     #[macro_export]
     macro_rules! set_frgb {
         () => {
-            print!("{}", super::X)
+            print!("{}", $crate::ansi::policy::paint($crate::ansi::X, $crate::ansi::policy::Stream::Stdout))
         };
         ($r:tt, $g:tt, $b:tt) => {
-            print!("\x1b[38;2;{};{};{}m", $r, $g, $b)
+            print!("{}", $crate::ansi::f_color::rgb([$r as usize, $g as usize, $b as usize]))
         }
     }
 
-    pub fn rgb([r, g, b]: [usize; 3]) -> String {
-        format!("\x1b[38;2;{};{};{}m", r, g, b)
+    /// Escape code for an RGB colour, turned into the closest colour the
+    /// terminal can show, see [`super::policy::depth`]. Components above
+    /// 255 are clamped to 255. Also written as `.#ff8800` in markup.
+    /// 
+    /// ---
+    /// ## Examples
+    /// ```
+    /// use utils::ansi::{f_color, policy::{self, ColorDepth, ColorMode}};
+    /// 
+    /// policy::set_mode(ColorMode::Always);
+    /// policy::set_depth(Some(ColorDepth::TrueColor));
+    /// assert_eq!(f_color::rgb([300, 136, 0]), f_color::rgb([255, 136, 0]));
+    /// ```
+    pub fn rgb(rgb: [usize; 3]) -> String {
+        super::rgb_code(rgb, false)
     }
-}
 
-pub const BBLK: &str = "\x1b[40m"; // Black
-pub const BRED: &str = "\x1b[41m"; // Red
-pub const BGRN: &str = "\x1b[42m"; // Green
-pub const BYLW: &str = "\x1b[43m"; // Yellow
-pub const BBLU: &str = "\x1b[44m"; // Blue
-pub const BMGT: &str = "\x1b[45m"; // Magenta
-pub const BCYN: &str = "\x1b[46m"; // Cyan
-pub const BWHT: &str = "\x1b[47m"; // White
+    /// Escape code for colour `index` of the 256 colour palette, see
+    /// [`super::palette`] for its layout. Also written as `.C0` to
+    /// `.C255` in markup.
+    /// 
+    /// ---
+    /// ## Examples
+    /// ```
+    /// use utils::ansi::{f_color, policy::{self, ColorDepth, ColorMode}};
+    /// 
+    /// policy::set_mode(ColorMode::Always);
+    /// policy::set_depth(Some(ColorDepth::Ansi256));
+    /// assert_eq!(f_color::ansi256(208), "\x1b[38;5;208m");
+    /// ```
+    pub fn ansi256(index: u8) -> String {
+        super::ansi256_code(index, false)
+    }
+}
 
 pub mod b_color { // Denoted by a #
 
     #[macro_export]
     macro_rules! set_brgb {
         () => {
-            print!("{}", super::X)
+            print!("{}", $crate::ansi::policy::paint($crate::ansi::X, $crate::ansi::policy::Stream::Stdout))
         };
         ($r:tt, $g:tt, $b:tt) => {
-            print!("\x1b[48;2;{};{};{}m", $r, $g, $b)
+            print!("{}", $crate::ansi::b_color::rgb([$r as usize, $g as usize, $b as usize]))
         }
     }
 
-    pub fn rgb([r, g, b]: [usize; 3]) -> String {
-        format!("\x1b[48;2;{};{};{}m", r, g, b)
+    /// Escape code for an RGB colour, turned into the closest colour the
+    /// terminal can show, see [`super::policy::depth`]. Components above
+    /// 255 are clamped to 255. Also written as `##ff8800` in markup.
+    /// 
+    /// ---
+    /// ## Examples
+    /// ```
+    /// use utils::ansi::{b_color, policy::{self, ColorDepth, ColorMode}};
+    /// 
+    /// policy::set_mode(ColorMode::Always);
+    /// policy::set_depth(Some(ColorDepth::TrueColor));
+    /// assert_eq!(b_color::rgb([300, 136, 0]), b_color::rgb([255, 136, 0]));
+    /// ```
+    pub fn rgb(rgb: [usize; 3]) -> String {
+        super::rgb_code(rgb, true)
+    }
+
+    /// Escape code for colour `index` of the 256 colour palette, see
+    /// [`super::palette`] for its layout. Also written as `#C0` to
+    /// `#C255` in markup.
+    /// 
+    /// ---
+    /// ## Examples
+    /// ```
+    /// use utils::ansi::{b_color, policy::{self, ColorDepth, ColorMode}};
+    /// 
+    /// policy::set_mode(ColorMode::Always);
+    /// policy::set_depth(Some(ColorDepth::Ansi256));
+    /// assert_eq!(b_color::ansi256(208), "\x1b[48;5;208m");
+    /// ```
+    pub fn ansi256(index: u8) -> String {
+        super::ansi256_code(index, true)
     }
 }
 
-pub const BLD: &str = "\x1b[1m";  // Bold
-pub const ITL: &str = "\x1b[3m";  // Italicized
-pub const UND: &str = "\x1b[4m";  // Underline
-// pub mod style { // Denoted by a _
-// }
+/// Escape code for an RGB colour as the foreground or `background`, turned
+/// into the closest colour the terminal can show. Components above 255 are
+/// clamped to 255.
+fn rgb_code(rgb: [usize; 3], background: bool) -> String {
+    let [r, g, b] = rgb.map(|c| c.min(255) as u8);
+
+    color_code(Color::Rgb(r, g, b), background)
+}
+
+/// Escape code for a colour of the 256 colour palette as the foreground or
+/// `background`, turned into the closest colour the terminal can show.
+fn ansi256_code(index: u8, background: bool) -> String {
+    color_code(Color::Ansi256(index), background)
+}
+
+fn color_code(color: Color, background: bool) -> String {
+    let style = if background {
+        Style::new().bg(color)
+    } else {
+        Style::new().fg(color)
+    };
+
+    style.to_string()
+}
 
 /// Function which replaces the custom ansi mapping strings
-/// with their respective ansi colour codes. This function
-/// is utilised by the [`super::colprint!`] and [`super::colprintln!`]
-/// macros
+/// with their respective ansi colour codes. The same markup is
+/// replaced at compile time in the format strings of the
+/// [`crate::colprint!`] family of macros, e.g. [`crate::colformat!`],
+/// which also understand the tags of [`markup::render`].
+/// 
+/// ---
+/// ## Markup
+/// A code is its sigil (`_`, `.` or `#`) directly followed by its name,
+/// see [`REPLACE_MAP`] for the full list. Codes are matched wherever they
+/// appear, longest name first, and need nothing to end them. This means
+/// `.GRNHello` is green `Hello`, but also that `_BLDER` is bold `ER` and
+/// `MAX_X` is `MAX` followed by a reset. The [`crate::colprint!`] macros
+/// catch words like `_BLDER` at compile time, this function does not.
+/// 
+/// Colours of the 256 colour palette, `.C0` to `.C255`, are the exception,
+/// they have to start a word or directly follow another code. So
+/// `.C208#C236` is two colours, but `file.C12` is left as text.
+/// 
+/// Any RGB colour can be written in hex, `.#ff8800` for the text, or by
+/// one of the [`palette::CSS_COLORS`] names, `.orange` or `#slategray`.
+/// Like a palette colour a CSS name has to start a word or follow a code,
+/// and unlike the other codes it has to be a whole word too, so both
+/// `report.red.txt` and `.orangey` are left as text. Either form can also
+/// be put in brackets, `.(orange)text` or `#(#202020)`, which is needed for
+/// a hex background and for a name next to text.
+/// 
+/// To print a code literally put a backslash in front of it, `\_BLDER`
+/// prints `_BLDER` and `report\.RED.txt` prints `report.RED.txt`. A doubled
+/// backslash in front of a code prints one backslash and keeps the code,
+/// so `\\_X` is a backslash followed by a reset. Backslashes anywhere else
+/// are left as they are.
+/// 
+/// *Note: In a normal string literal the backslash itself has to be
+/// escaped, so either write `"\\_X"` or use a raw string `r"\_X"`.*
+/// 
+/// The codes are written whether or not colour is enabled, but palette and
+/// RGB colours the terminal can't show are turned into the closest colour
+/// it can, see [`policy::depth`].
 /// 
 /// ---
 /// ## Examples
@@ -79,164 +287,150 @@ pub const UND: &str = "\x1b[4m";  // Underline
 /// let output = replace_cc(String::from("_BLD_ITL.GRNHello, world!_X"));
 /// assert_eq!(output, manual);
 /// ```
+/// Colours from the 256 colour palette:
+/// ```
+/// use utils::ansi::{replace_cc, policy::{self, ColorDepth}};
+/// 
+/// policy::set_depth(Some(ColorDepth::Ansi256));
+/// let output = replace_cc(String::from(".C208#C236Orange on grey_X"));
+/// assert_eq!(output, "\x1b[38;5;208m\x1b[48;5;236mOrange on grey\x1b[0m");
+/// ```
+/// Hex and CSS colours:
+/// ```
+/// use utils::ansi::{replace_cc, policy::{self, ColorDepth}};
+/// 
+/// policy::set_depth(Some(ColorDepth::TrueColor));
+/// let output = replace_cc(String::from(".#ff8800#(#202020)Orange on grey_X"));
+/// assert_eq!(output, "\x1b[38;2;255;136;0m\x1b[48;2;32;32;32mOrange on grey\x1b[0m");
+/// 
+/// let output = replace_cc(String::from(".(orange)Orange#(slategray) on slate_X"));
+/// assert_eq!(output, "\x1b[38;2;255;165;0mOrange\x1b[48;2;112;128;144m on slate\x1b[0m");
+/// 
+/// let output = replace_cc(String::from("report.red.txt"));
+/// assert_eq!(output, "report.red.txt");
+/// 
+/// policy::set_depth(Some(ColorDepth::Ansi16));
+/// let output = replace_cc(String::from(".#ff8800Orange_X"));
+/// assert_eq!(output, "\x1b[91mOrange\x1b[0m");
+/// ```
+/// Escaping codes:
+/// ```
+/// use utils::ansi::replace_cc;
+/// 
+/// let output = replace_cc(String::from(r"MAX\_X = 10, see report\.RED.txt"));
+/// assert_eq!(output, "MAX_X = 10, see report.RED.txt");
+/// 
+/// let output = replace_cc(String::from(r"C:\\_Xdir"));
+/// assert_eq!(output, "C:\\\x1b[0mdir");
+/// ```
 pub fn replace_cc(s: String) -> String {
-    let mut s = s;
+    let mut out = String::with_capacity(s.len());
 
-    for (from, to) in REPLACE_MAP.iter() {
-        s = s.replace(from, to);
-    }
+    replace_cc_into(&s, &mut out).expect("writing to a String cannot fail");
 
-    s
+    out
 }
 
-pub mod cursor {
-    pub const HOME:  &str = "\x1b[H";
-    pub const LN_UP: &str = "\x1bM";
-
-    // moves cursor to home position (0, 0)
-    pub fn reset() {
-        print!("{HOME}");
-    }
-
-    // moves cursor to line #, column #
-    pub fn set_pos([line, column]: [usize; 2]) {
-        print!("\x1b[{line};{column}H");
-    }
-
-    // moves cursor up # lines
-    pub fn up(lines: usize) {
-        print!("\x1b[{lines}A");
-    }
-
-    // moves cursor down # lines
-    pub fn down(lines: usize) {
-        print!("\x1b[{lines}B");
-    }
-
-    // moves cursor right # columns
-    pub fn right(columns: usize) {
-        print!("\x1b[{columns}C");
-    }
-
-    // moves cursor left # columns
-    pub fn left(columns: usize) {
-        print!("\x1b[{columns}D");
-    }
-
-    // moves cursor to beginning of next line, # lines down
-    pub fn new_lines_down(lines: usize) {
-        print!("\x1b[{lines}E");
-    }
-
-    // moves cursor to beginning of previous line, # lines up
-    pub fn lines_up(lines: usize) {
-        print!("\x1b[{lines}F");
-    }
+/// Non-allocating version of [`replace_cc`] which writes the string, with
+/// the custom ansi mapping strings replaced, into any [`fmt::Write`].
+/// 
+/// The markup is scanned in a single pass, so this is the function to
+/// reach for when colouring a lot of output.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::replace_cc_into;
+/// 
+/// let mut output = String::new();
+/// replace_cc_into("_BLD.REDError:_X file not found", &mut output).unwrap();
+/// assert_eq!(output, "\x1b[1m\x1b[31mError:\x1b[0m file not found");
+/// ```
+pub fn replace_cc_into(s: &str, w: &mut impl fmt::Write) -> fmt::Result {
+    let depth = policy::depth();
 
-    // moves cursor to column #
-    pub fn set_column(column: usize) {
-        print!("\x1b[{column}G");
+    for token in Tokens::new(s) {
+        match token {
+            Token::Text(text) => w.write_str(text)?,
+            Token::Code(code) => w.write_str(code)?,
+            token => token.write_at(depth, w)?,
+        }
     }
 
-    // moves cursor one line up and scrolls if needed
-    pub fn one_line_up() {
-        print!("{LN_UP}");
-    }
+    Ok(())
 }
 
-
-/// Macro which automatically uses [`ansi::replace_cc`] to
-/// replace custom codes with ansi color escape codes and
-/// [`print!`]s it to the stdout.
+/// Function which removes the custom ansi mapping strings, leaving only
+/// the text. Escaped codes are unescaped just like in [`replace_cc`].
 /// 
 /// ---
-/// # Examples
+/// ## Examples
 /// ```
-/// use utils::colprint;
+/// use utils::ansi::strip_cc;
 /// 
-/// colprint!("#CYN.WHT_BLD_ITLHello, world!_X");
+/// let output = strip_cc(r"_BLD.REDError:_X \_X is not set");
+/// assert_eq!(output, "Error: _X is not set");
 /// ```
-/// ---
-/// # Codes
-/// | Text | To  | Color / Style |
-/// | ---- | --- | ------------- |
-/// | `_X` | \x1b[0m | Clear ANSI formatting |
-/// | `_BLD` | `\x1b[1m` | Bold |
-/// | `_ITL` | `\x1b[3m` | Italicized |
-/// | `_UND` | `\x1b[4m` | Underline |
-/// | `.BLK` | `\x1b[30m` | Text Black |
-/// | `.RED` | `\x1b[31m` | Text Red |
-/// | `.GRN` | `\x1b[32m` | Text Green |
-/// | `.YLW` | `\x1b[33m` | Text Yellow |
-/// | `.BLU` | `\x1b[34m` | Text Blue |
-/// | `.MGT` | `\x1b[35m` | Text Magenta |
-/// | `.CYN` | `\x1b[36m` | Text Cyan |
-/// | `.WHT` | `\x1b[37m` | Text White |
-/// | `#BLK` | `\x1b[40m` | Background Black |
-/// | `#RED` | `\x1b[41m` | Background Red |
-/// | `#GRN` | `\x1b[42m` | Background Green |
-/// | `#YLW` | `\x1b[43m` | Background Yellow |
-/// | `#BLU` | `\x1b[44m` | Background Blue |
-/// | `#MGT` | `\x1b[45m` | Background Magenta |
-/// | `#CYN` | `\x1b[46m` | Background Cyan |
-/// | `#WHT` | `\x1b[47m` | Background White |
-/// 
-#[macro_export]
-#[cfg(feature = "ansi")]
-macro_rules! colprint {
-    () => {
-        print!()
-    };
-    ($($arg:tt)*) => {{
-        let txt = utils::ansi::replace_cc(format!($($arg)*));
-        print!("{txt}")
-    }}
+pub fn strip_cc(s: &str) -> String {
+    Tokens::new(s)
+        .filter_map(|token| match token {
+            Token::Text(text) => Some(text),
+            _ => None,
+        })
+        .collect()
 }
 
-/// Macro which automatically uses [`ansi::replace_cc`] to
-/// replace custom codes with ansi color escape codes and
-/// [`println!`]s it to the stdout.
+/// Moves, saves, restores and hides the cursor of the terminal. Each is a
+/// type which writes its escape sequence with `Display`, so they can go
+/// into a `format!`, a buffer or any writer through [`cursor::CursorExt`].
+/// The functions such as [`cursor::up`] print them straight to the stdout.
+/// 
+/// [`cursor::HiddenCursor`] keeps the cursor hidden while it's alive and
+/// [`cursor::position`] asks the terminal where the cursor is.
 /// 
 /// ---
-/// # Examples
+/// ## Examples
+/// Redrawing a progress line in place:
+/// ```
+/// use utils::ansi::cursor::{Column, Up};
+/// 
+/// let frame = format!("{}{}[#####     ] 50%", Up(1), Column(1));
+/// assert_eq!(frame, "\x1b[1A\x1b[1G[#####     ] 50%");
+/// ```
+/// Moving by 0 writes nothing, unless written in the alternate form, like
+/// [`cursor::up`] does:
 /// ```
-/// use utils::colprintln;
+/// use utils::ansi::cursor::Up;
 /// 
-/// colprintln!("#CYN.WHT_BLD_ITLHello, world!_X");
+/// assert_eq!(Up(0).to_string(), "");
+/// assert_eq!(format!("{:#}", Up(0)), "\x1b[0A");
+/// assert_eq!(format!("{:#}", Up(3)), "\x1b[3A");
 /// ```
+pub mod cursor;
+
+/// Clears the screen or lines of it, switches to the alternate screen and
+/// scrolls, with the same design as [`cursor`]. Each is a type which
+/// writes its escape sequence with `Display`, or is written to any writer
+/// through [`screen::ScreenExt`].
+/// 
 /// ---
-/// # Codes
-/// | Text | To  | Color / Style |
-/// | ---- | --- | ------------- |
-/// | `_X` | \x1b[0m | Clear ANSI formatting |
-/// | `_BLD` | `\x1b[1m` | Bold |
-/// | `_ITL` | `\x1b[3m` | Italicized |
-/// | `_UND` | `\x1b[4m` | Underline |
-/// | `.BLK` | `\x1b[30m` | Text Black |
-/// | `.RED` | `\x1b[31m` | Text Red |
-/// | `.GRN` | `\x1b[32m` | Text Green |
-/// | `.YLW` | `\x1b[33m` | Text Yellow |
-/// | `.BLU` | `\x1b[34m` | Text Blue |
-/// | `.MGT` | `\x1b[35m` | Text Magenta |
-/// | `.CYN` | `\x1b[36m` | Text Cyan |
-/// | `.WHT` | `\x1b[37m` | Text White |
-/// | `#BLK` | `\x1b[40m` | Background Black |
-/// | `#RED` | `\x1b[41m` | Background Red |
-/// | `#GRN` | `\x1b[42m` | Background Green |
-/// | `#YLW` | `\x1b[43m` | Background Yellow |
-/// | `#BLU` | `\x1b[44m` | Background Blue |
-/// | `#MGT` | `\x1b[45m` | Background Magenta |
-/// | `#CYN` | `\x1b[46m` | Background Cyan |
-/// | `#WHT` | `\x1b[47m` | Background White |
-/// 
-#[cfg(feature = "ansi")]
-#[macro_export]
-macro_rules! colprintln {
-    () => {
-        println!()
-    };
-    ($($arg:tt)*) => {{
-        let txt = utils::ansi::replace_cc(format!($($arg)*));
-        println!("{txt}")
-    }}
-}
\ No newline at end of file
+/// ## Examples
+/// A status line kept at the bottom of a 24 line terminal while the rest
+/// scrolls:
+/// ```
+/// use utils::ansi::{cursor::MoveTo, screen::{ClearLine, ScrollRegion}};
+/// 
+/// let frame = format!("{}{}{}building...", ScrollRegion(1, 23), MoveTo(24, 1), ClearLine::All);
+/// assert_eq!(frame, "\x1b[1;23r\x1b[24;1H\x1b[2Kbuilding...");
+/// ```
+pub mod screen;
+
+/// Reads the size of the terminal and switches it between the modes of
+/// handing input to the program, see [`terminal::size`] and
+/// [`terminal::ModeGuard`].
+pub mod terminal;
+
+/// Reads the keys pressed, decoding arrows, function keys, modifiers and
+/// the other escape sequences the terminal sends for them into
+/// [`input::KeyEvent`]s, see [`input::Keys`].
+pub mod input;
@@ -0,0 +1,501 @@
+use std::fmt;
+use std::io::{self, IsTerminal};
+use std::time::Duration;
+
+pub const HOME:  &str = "\x1b[H";
+pub const LN_UP: &str = "\x1bM";
+
+/// Moves the cursor to the home position, the top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Home;
+
+impl fmt::Display for Home {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(HOME)
+    }
+}
+
+/// Moves the cursor to a line and a column, both starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MoveTo(pub usize, pub usize);
+
+impl fmt::Display for MoveTo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[{};{}H", self.0, self.1)
+    }
+}
+
+/// Moves the cursor to a column of the line it's on, starting at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Column(pub usize);
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[{}G", self.0)
+    }
+}
+
+/// Moves the cursor one line up, scrolling the screen down if it's on the
+/// top line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReverseIndex;
+
+impl fmt::Display for ReverseIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(LN_UP)
+    }
+}
+
+/// Saves the position of the cursor along with the style and character
+/// set, to be gone back to with [`Restore`], `DECSC`. This is understood
+/// by more terminals than [`SavePosition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Save;
+
+impl fmt::Display for Save {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b7")
+    }
+}
+
+/// Goes back to what was saved by [`Save`], `DECRC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Restore;
+
+impl fmt::Display for Restore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b8")
+    }
+}
+
+/// Saves only the position of the cursor, to be gone back to with
+/// [`RestorePosition`], `CSI s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SavePosition;
+
+impl fmt::Display for SavePosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[s")
+    }
+}
+
+/// Goes back to the position saved by [`SavePosition`], `CSI u`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RestorePosition;
+
+impl fmt::Display for RestorePosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[u")
+    }
+}
+
+/// Hides the cursor, see [`HiddenCursor`] to have it shown again
+/// whatever happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hide;
+
+impl fmt::Display for Hide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[?25l")
+    }
+}
+
+/// Shows the cursor after [`Hide`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Show;
+
+impl fmt::Display for Show {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[?25h")
+    }
+}
+
+/// Defines a movement by a number of lines or columns, which is written
+/// as nothing for 0 as terminals read a count of 0 as 1.
+macro_rules! movement {
+    ($(#[$doc:meta])* $name:ident, $letter:literal) => {
+        $(#[$doc])*
+        #[doc = ""]
+        #[doc = "Nothing is written for 0, which terminals would read as 1. The alternate"]
+        #[doc = "form, `{:#}`, writes the count as given, as the older print functions"]
+        #[doc = "such as [`up`] do."]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name(pub usize);
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self.0 {
+                    0 if !f.alternate() => Ok(()),
+                    n => write!(f, concat!("\x1b[{}", $letter), n),
+                }
+            }
+        }
+    };
+}
+
+movement!(
+    /// Moves the cursor up a number of lines, stopping at the top.
+    Up, "A"
+);
+movement!(
+    /// Moves the cursor down a number of lines, stopping at the bottom.
+    Down, "B"
+);
+movement!(
+    /// Moves the cursor right a number of columns, stopping at the edge.
+    Right, "C"
+);
+movement!(
+    /// Moves the cursor left a number of columns, stopping at the edge.
+    Left, "D"
+);
+movement!(
+    /// Moves the cursor to the start of the line a number of lines down.
+    NextLine, "E"
+);
+movement!(
+    /// Moves the cursor to the start of the line a number of lines up.
+    PrevLine, "F"
+);
+
+/// Extension trait for moving the cursor by writing to any
+/// [`io::Write`], such as the stderr, a locked stdout or a buffer holding
+/// a whole frame.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use std::io::Write;
+/// use utils::ansi::cursor::CursorExt;
+/// 
+/// let mut frame = Vec::new();
+/// frame.move_to(2, 5)?;
+/// frame.write_all(b"Done")?;
+/// frame.move_up(1)?;
+/// assert_eq!(frame, b"\x1b[2;5HDone\x1b[1A");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait CursorExt: io::Write {
+    /// Writes [`Home`].
+    fn move_home(&mut self) -> io::Result<()> {
+        write!(self, "{Home}")
+    }
+
+    /// Writes [`MoveTo`].
+    fn move_to(&mut self, line: usize, column: usize) -> io::Result<()> {
+        write!(self, "{}", MoveTo(line, column))
+    }
+
+    /// Writes [`Column`].
+    fn move_to_column(&mut self, column: usize) -> io::Result<()> {
+        write!(self, "{}", Column(column))
+    }
+
+    /// Writes [`Up`].
+    fn move_up(&mut self, lines: usize) -> io::Result<()> {
+        write!(self, "{}", Up(lines))
+    }
+
+    /// Writes [`Down`].
+    fn move_down(&mut self, lines: usize) -> io::Result<()> {
+        write!(self, "{}", Down(lines))
+    }
+
+    /// Writes [`Right`].
+    fn move_right(&mut self, columns: usize) -> io::Result<()> {
+        write!(self, "{}", Right(columns))
+    }
+
+    /// Writes [`Left`].
+    fn move_left(&mut self, columns: usize) -> io::Result<()> {
+        write!(self, "{}", Left(columns))
+    }
+
+    /// Writes [`NextLine`].
+    fn move_to_next_line(&mut self, lines: usize) -> io::Result<()> {
+        write!(self, "{}", NextLine(lines))
+    }
+
+    /// Writes [`PrevLine`].
+    fn move_to_prev_line(&mut self, lines: usize) -> io::Result<()> {
+        write!(self, "{}", PrevLine(lines))
+    }
+
+    /// Writes [`ReverseIndex`].
+    fn reverse_index(&mut self) -> io::Result<()> {
+        write!(self, "{ReverseIndex}")
+    }
+
+    /// Writes [`Save`].
+    fn save_cursor(&mut self) -> io::Result<()> {
+        write!(self, "{Save}")
+    }
+
+    /// Writes [`Restore`].
+    fn restore_cursor(&mut self) -> io::Result<()> {
+        write!(self, "{Restore}")
+    }
+
+    /// Writes [`SavePosition`].
+    fn save_position(&mut self) -> io::Result<()> {
+        write!(self, "{SavePosition}")
+    }
+
+    /// Writes [`RestorePosition`].
+    fn restore_position(&mut self) -> io::Result<()> {
+        write!(self, "{RestorePosition}")
+    }
+
+    /// Writes [`Hide`].
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        write!(self, "{Hide}")
+    }
+
+    /// Writes [`Show`].
+    fn show_cursor(&mut self) -> io::Result<()> {
+        write!(self, "{Show}")
+    }
+}
+
+impl<W: io::Write + ?Sized> CursorExt for W {}
+
+/// Writer which hides the cursor until it's dropped, showing it again even
+/// if the thread panics. Everything written to it goes to the writer it
+/// holds.
+/// 
+/// *Note: The cursor isn't shown again if the program exits without
+/// unwinding, such as with [`std::process::exit`] or `panic = "abort"`.*
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use std::io::{self, Write};
+/// use utils::ansi::cursor::{CursorExt, HiddenCursor};
+/// 
+/// let mut out = HiddenCursor::new(Vec::new())?;
+/// for percent in [0, 50, 100] {
+///     out.move_to_column(1)?;
+///     write!(out, "{percent:>3}%")?;
+/// }
+/// # Ok::<(), io::Error>(())
+/// ```
+/// With the stdout:
+/// ```no_run
+/// use std::io::{self, Write};
+/// use utils::ansi::cursor::HiddenCursor;
+/// 
+/// let mut stdout = HiddenCursor::new(io::stdout().lock())?;
+/// writeln!(stdout, "Working...")?;
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct HiddenCursor<W: io::Write> {
+    writer: W,
+}
+
+impl<W: io::Write> HiddenCursor<W> {
+    /// Hides the cursor by writing [`Hide`] to `writer`.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.hide_cursor()?;
+        writer.flush()?;
+        Ok(Self { writer })
+    }
+
+    /// The writer the cursor was hidden on.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// The writer the cursor was hidden on.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<W: io::Write> io::Write for HiddenCursor<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write> Drop for HiddenCursor<W> {
+    fn drop(&mut self) {
+        // Errors can't be returned here, and the cursor is shown again at
+        // the latest when the terminal is reset.
+        let _ = self.writer.show_cursor();
+        let _ = self.writer.flush();
+    }
+}
+
+/// The line and column of the cursor, both starting at 1, asked from the
+/// terminal with `CSI 6n`. If the terminal doesn't answer within half a
+/// second an error of [`io::ErrorKind::TimedOut`] is returned, see
+/// [`position_timeout`] to wait for longer. If the stdin ends or hangs up
+/// first an error of [`io::ErrorKind::UnexpectedEof`] is returned.
+/// 
+/// Both the stdin and the stdout have to be the terminal, otherwise an
+/// error of [`io::ErrorKind::Unsupported`] is returned, as it is on
+/// platforms other than unix. Input typed before the answer arrives is
+/// thrown away.
+/// 
+/// ---
+/// ## Examples
+/// ```no_run
+/// use utils::ansi::cursor;
+/// 
+/// let (line, column) = cursor::position()?;
+/// println!("The cursor was at {line}:{column}");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn position() -> io::Result<(usize, usize)> {
+    position_timeout(Duration::from_millis(500))
+}
+
+/// Version of [`position`] which waits for the answer of the terminal for
+/// up to `timeout`.
+pub fn position_timeout(timeout: Duration) -> io::Result<(usize, usize)> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err(io::Error::new(io::ErrorKind::Unsupported, "the stdin and stdout must be a terminal"));
+    }
+
+    query_position(timeout)
+}
+
+/// Reads the answer to `CSI 6n`, `CSI line ; column R`, from the end of
+/// `input`.
+fn parse_position(input: &[u8]) -> Option<(usize, usize)> {
+    let end = input.iter().rposition(|&b| b == b'R')?;
+    let start = input[..end].windows(2).rposition(|w| w == b"\x1b[")?;
+    let (line, column) = std::str::from_utf8(&input[start + 2..end]).ok()?.split_once(';')?;
+
+    Some((line.parse().ok()?, column.parse().ok()?))
+}
+
+#[cfg(unix)]
+fn query_position(timeout: Duration) -> io::Result<(usize, usize)> {
+    use std::io::Write;
+    use std::time::Instant;
+
+    let fd = libc::STDIN_FILENO;
+
+    // The answer is read as soon as it arrives, without being echoed.
+    let _mode = super::terminal::cbreak_mode()?;
+
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(b"\x1b[6n")?;
+    stdout.flush()?;
+
+    let deadline = Instant::now() + timeout;
+    let mut input = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "the terminal didn't report the cursor position"));
+        }
+
+        let mut poll = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+        let millis = remaining.as_millis().clamp(1, libc::c_int::MAX as u128) as libc::c_int;
+        // SAFETY: `poll` is a single valid `pollfd`.
+        match unsafe { libc::poll(&mut poll, 1, millis) } {
+            0 => continue,
+            -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+            -1 => return Err(io::Error::last_os_error()),
+            _ => (),
+        }
+
+        let mut buf = [0u8; 64];
+        // SAFETY: `buf` is valid for writes of its whole length.
+        let read = unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) };
+        match read {
+            -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+            -1 => return Err(io::Error::last_os_error()),
+            // Polling an ended stdin returns straight away, so waiting for
+            // the answer would spin until the timeout.
+            0 => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the stdin ended before the cursor position")),
+            read => input.extend_from_slice(&buf[..read as usize]),
+        }
+
+        if let Some(position) = parse_position(&input) {
+            return Ok(position);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn query_position(_timeout: Duration) -> io::Result<(usize, usize)> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "the cursor position can only be read on unix"))
+}
+
+// moves cursor to home position (0, 0)
+pub fn reset() {
+    print!("{Home}");
+}
+
+// moves cursor to line #, column #
+pub fn set_pos([line, column]: [usize; 2]) {
+    print!("{}", MoveTo(line, column));
+}
+
+// moves cursor up # lines
+pub fn up(lines: usize) {
+    print!("{:#}", Up(lines));
+}
+
+// moves cursor down # lines
+pub fn down(lines: usize) {
+    print!("{:#}", Down(lines));
+}
+
+// moves cursor right # columns
+pub fn right(columns: usize) {
+    print!("{:#}", Right(columns));
+}
+
+// moves cursor left # columns
+pub fn left(columns: usize) {
+    print!("{:#}", Left(columns));
+}
+
+// moves cursor to beginning of next line, # lines down
+pub fn new_lines_down(lines: usize) {
+    print!("{:#}", NextLine(lines));
+}
+
+// moves cursor to beginning of previous line, # lines up
+pub fn lines_up(lines: usize) {
+    print!("{:#}", PrevLine(lines));
+}
+
+// moves cursor to column #
+pub fn set_column(column: usize) {
+    print!("{}", Column(column));
+}
+
+// moves cursor one line up and scrolls if needed
+pub fn one_line_up() {
+    print!("{ReverseIndex}");
+}
+
+// saves cursor position and style
+pub fn save() {
+    print!("{Save}");
+}
+
+// restores cursor position and style saved by save()
+pub fn restore() {
+    print!("{Restore}");
+}
+
+// hides cursor
+pub fn hide() {
+    print!("{Hide}");
+}
+
+// shows cursor
+pub fn show() {
+    print!("{Show}");
+}
@@ -0,0 +1,449 @@
+use super::palette::ColorDepth;
+use super::spans::{self, Sequence, Span};
+use super::{markup, palette, Attribute, Color, Style};
+
+/// The names of the 16 standard and bright colours in class names, such as
+/// `ansi-fg-bright-red`.
+const NAMES: [&str; 16] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    "bright-black", "bright-red", "bright-green", "bright-yellow",
+    "bright-blue", "bright-magenta", "bright-cyan", "bright-white",
+];
+
+/// The text decorations which can be combined, with their class and CSS
+/// line.
+const LINES: [(&str, &str); 3] = [
+    ("underline", "underline"),
+    ("strikethrough", "line-through"),
+    ("overline", "overline"),
+];
+
+/// The colours of text without a colour of its own and of the page behind
+/// it, which reversed text swaps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    /// The default foreground and background colours.
+    fn colors(self) -> ([u8; 3], [u8; 3]) {
+        match self {
+            Theme::Dark => ([229, 229, 229], [30, 30, 30]),
+            Theme::Light => ([30, 30, 30], [255, 255, 255]),
+        }
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Escapes the characters of `s` which have a meaning in HTML.
+fn escape(s: &str, out: &mut String) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// The url of `seq` if it is an OSC 8 hyperlink, which is empty at the end
+/// of the link.
+fn link_url(seq: Sequence<'_>) -> Option<&str> {
+    let Sequence::Osc(s) = seq else {
+        return None;
+    };
+    let s = s.strip_prefix("\x1b]8;")?;
+    let s = s.strip_suffix("\x1b\\").or_else(|| s.strip_suffix('\x07'))?;
+
+    s.split_once(';').map(|(_, url)| url)
+}
+
+/// The schemes of the urls which are written as links, as the text being
+/// converted can't be trusted with `javascript:` or `data:` urls.
+const SCHEMES: [&str; 4] = ["http", "https", "file", "mailto"];
+
+/// Whether `url` has one of the [`SCHEMES`].
+fn is_safe_url(url: &str) -> bool {
+    url.split_once(':')
+        .is_some_and(|(scheme, _)| SCHEMES.iter().any(|s| s.eq_ignore_ascii_case(scheme)))
+}
+
+/// Converter of coloured text into HTML, with each run of styled text in a
+/// `<span>`.
+/// 
+/// By default the styles are written inline and the output is a fragment
+/// to be put in a `<pre>`. With [`Html::classes`] the standard and bright
+/// colours and styles are written as classes instead, which need the CSS
+/// of [`Html::stylesheet`]. With [`Html::standalone`] the output is a whole
+/// page, including that CSS.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::html::Html;
+/// 
+/// let output = Html::new().convert("\x1b[1;31mError:\x1b[0m <none>");
+/// assert_eq!(output, r#"<span style="color:#cd0000;font-weight:bold">Error:</span> &lt;none&gt;"#);
+/// 
+/// let output = Html::new().classes().convert_markup("[bold red]Error:[/] <none>");
+/// assert_eq!(output, r#"<span class="ansi-fg-red ansi-bold">Error:</span> &lt;none&gt;"#);
+/// ```
+/// A whole page for a CI artifact:
+/// ```
+/// use utils::ansi::html::{Html, Theme};
+/// 
+/// let page = Html::new().theme(Theme::Light).standalone("Test log").convert("\x1b[32mok\x1b[0m");
+/// assert!(page.starts_with("<!DOCTYPE html>"));
+/// assert!(page.contains("<title>Test log</title>"));
+/// assert!(page.contains(r#"<pre class="ansi"><span style="color:#00cd00">ok</span></pre>"#));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Html {
+    classes: bool,
+    theme: Theme,
+    /// The title of the page, if the output is a whole page.
+    title: Option<String>,
+}
+
+impl Html {
+    /// A converter writing inline styles, in the dark theme.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes the standard and bright colours and the styles as classes,
+    /// such as `ansi-fg-red` and `ansi-bold`. 256 and RGB colours are still
+    /// written inline.
+    pub fn classes(mut self) -> Self {
+        self.classes = true;
+        self
+    }
+
+    /// Sets the theme, which gives the colours of reversed text without a
+    /// colour of its own and of a standalone page.
+    pub fn theme(mut self, theme: Theme) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// Writes a whole page with `title`, with the text in a `<pre>`.
+    pub fn standalone(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Converts text containing ansi escape sequences. OSC 8 hyperlinks
+    /// to `http`, `https`, `file` and `mailto` urls become `<a>` links,
+    /// with the text of any other link written as is. Other escape
+    /// sequences which aren't colours or styles, such as cursor movement,
+    /// are left out.
+    pub fn convert(&self, s: &str) -> String {
+        let mut links = Vec::new();
+        let spans = spans::parse_with(s, |at, seq| {
+            if let Some(url) = link_url(seq) {
+                links.push((at, url));
+            }
+        });
+
+        self.write(&spans, &links)
+    }
+
+    /// Converts text written in the markup of [`super::markup::render`],
+    /// the same markup as the [`crate::colprint!`] macros. Unlike `render`
+    /// the colours are kept as they are written and links are always
+    /// links, whatever the terminal this runs in.
+    pub fn convert_markup(&self, s: &str) -> String {
+        let mut ansi = String::with_capacity(s.len());
+        markup::render_at(s, ColorDepth::TrueColor, true, &mut ansi).expect("writing to a String cannot fail");
+
+        self.convert(&ansi)
+    }
+
+    /// Converts spans of styled text, see [`super::spans`].
+    pub fn convert_spans(&self, spans: &[Span]) -> String {
+        self.write(spans, &[])
+    }
+
+    /// Writes the spans, with the links starting or, with an empty url,
+    /// ending at each byte offset into their text. Only links with one of
+    /// the [`SCHEMES`] are written.
+    fn write(&self, spans: &[Span], links: &[(usize, &str)]) -> String {
+        let mut out = String::new();
+
+        if let Some(title) = &self.title {
+            out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>");
+            escape(title, &mut out);
+            out.push_str("</title>\n<style>\n");
+            out.push_str(&self.stylesheet());
+            out.push_str("</style>\n</head>\n<body>\n<pre class=\"ansi\">");
+        }
+
+        let mut links = links.iter().peekable();
+        let (mut at, mut open) = (0, false);
+        for span in spans {
+            let mut text = span.text.as_str();
+            loop {
+                while let Some((_, url)) = links.next_if(|(start, _)| *start <= at) {
+                    if open {
+                        out.push_str("</a>");
+                    }
+                    // Links to any other scheme are written as plain text.
+                    open = is_safe_url(url);
+                    if open {
+                        out.push_str("<a href=\"");
+                        escape(url, &mut out);
+                        out.push_str("\">");
+                    }
+                }
+
+                // Spans are split where a link starts or ends inside them.
+                let len = links.peek().map_or(text.len(), |(start, _)| text.len().min(start - at));
+                let (part, rest) = text.split_at(len);
+                self.write_span(span.style, part, &mut out);
+                (at, text) = (at + len, rest);
+
+                if text.is_empty() {
+                    break;
+                }
+            }
+        }
+        if open {
+            out.push_str("</a>");
+        }
+
+        if self.title.is_some() {
+            out.push_str("</pre>\n</body>\n</html>\n");
+        }
+
+        out
+    }
+
+    /// The CSS for the classes written by [`Html::classes`], the blinking
+    /// animation and the `ansi` class of the `<pre>` of a standalone page.
+    pub fn stylesheet(&self) -> String {
+        let (fg, bg) = self.theme.colors();
+        let mut css = String::new();
+
+        css.push_str(&format!("body {{ margin: 0; background-color: {}; }}\n", hex(bg)));
+        css.push_str(&format!(
+            ".ansi {{ margin: 0; padding: 1em; font-family: monospace; color: {}; background-color: {}; }}\n",
+            hex(fg), hex(bg),
+        ));
+
+        for (i, name) in NAMES.iter().enumerate() {
+            let color = hex(palette::ANSI16[i]);
+            css.push_str(&format!(".ansi-fg-{name} {{ color: {color}; }}\n"));
+            css.push_str(&format!(".ansi-bg-{name} {{ background-color: {color}; }}\n"));
+        }
+        css.push_str(&format!(".ansi-fg-inverse {{ color: {}; }}\n", hex(bg)));
+        css.push_str(&format!(".ansi-bg-inverse {{ background-color: {}; }}\n", hex(fg)));
+
+        css.push_str(".ansi-bold { font-weight: bold; }\n");
+        css.push_str(".ansi-dim { opacity: 0.5; }\n");
+        css.push_str(".ansi-italic { font-style: italic; }\n");
+        css.push_str(".ansi-hidden { visibility: hidden; }\n");
+        css.push_str(".ansi-blink { animation: ansi-blink 1s step-end infinite; }\n");
+        css.push_str("@keyframes ansi-blink { 50% { opacity: 0; } }\n");
+
+        // Every combination of lines, as each sets all of the lines at once.
+        for mask in 1..1 << LINES.len() {
+            let lines = LINES.iter().enumerate().filter(|(i, _)| mask & 1 << i != 0);
+            let selector: Vec<_> = lines.clone().map(|(_, (class, _))| format!(".ansi-{class}")).collect();
+            let line: Vec<_> = lines.map(|(_, (_, line))| *line).collect();
+            css.push_str(&format!("{} {{ text-decoration-line: {}; }}\n", selector.concat(), line.join(" ")));
+        }
+        css.push_str(".ansi-double-underline { text-decoration-style: double; }\n");
+
+        css
+    }
+
+    fn write_span(&self, style: Style, text: &str, out: &mut String) {
+        if style.is_plain() {
+            return escape(text, out);
+        }
+
+        let mut classes = Vec::new();
+        let mut styles = Vec::new();
+
+        let (mut fg, mut bg) = (style.foreground(), style.background());
+        let reverse = style.has(Attribute::Reverse);
+        if reverse {
+            (fg, bg) = (bg, fg);
+        }
+        self.write_color(fg, false, reverse, &mut classes, &mut styles);
+        self.write_color(bg, true, reverse, &mut classes, &mut styles);
+
+        for attr in style.attributes() {
+            let (class, css) = match attr {
+                Attribute::Bold => ("bold", "font-weight:bold"),
+                Attribute::Dim => ("dim", "opacity:0.5"),
+                Attribute::Italic => ("italic", "font-style:italic"),
+                Attribute::Hidden => ("hidden", "visibility:hidden"),
+                Attribute::Blink => ("blink", "animation:ansi-blink 1s step-end infinite"),
+                Attribute::DoubleUnderline => ("double-underline", "text-decoration-style:double"),
+                // The lines are added together below, and reversing swaps
+                // the colours above.
+                _ => continue,
+            };
+
+            if self.classes {
+                classes.push(format!("ansi-{class}"));
+            } else {
+                styles.push(css.to_string());
+            }
+        }
+
+        let lines: Vec<_> = [
+            style.has(Attribute::Underline) || style.has(Attribute::DoubleUnderline),
+            style.has(Attribute::Strikethrough),
+            style.has(Attribute::Overline),
+        ]
+        .into_iter()
+        .zip(LINES)
+        .filter_map(|(has, line)| has.then_some(line))
+        .collect();
+
+        if self.classes {
+            classes.extend(lines.iter().map(|(class, _)| format!("ansi-{class}")));
+        } else if !lines.is_empty() {
+            let lines: Vec<_> = lines.iter().map(|(_, line)| *line).collect();
+            styles.push(format!("text-decoration-line:{}", lines.join(" ")));
+        }
+
+        out.push_str("<span");
+        if !classes.is_empty() {
+            out.push_str(&format!(" class=\"{}\"", classes.join(" ")));
+        }
+        if !styles.is_empty() {
+            out.push_str(&format!(" style=\"{}\"", styles.join(";")));
+        }
+        out.push('>');
+        escape(text, out);
+        out.push_str("</span>");
+    }
+
+    /// Adds the colour of the foreground or `background`. Reversed text
+    /// without a colour uses the default colour of the other side.
+    fn write_color(
+        &self,
+        color: Option<Color>,
+        background: bool,
+        reverse: bool,
+        classes: &mut Vec<String>,
+        styles: &mut Vec<String>,
+    ) {
+        let (layer, property) = if background {
+            ("bg", "background-color")
+        } else {
+            ("fg", "color")
+        };
+
+        match color {
+            Some(color) => match color.ansi16_index() {
+                Some(index) if self.classes => classes.push(format!("ansi-{layer}-{}", NAMES[index as usize])),
+                _ => styles.push(format!("{property}:{}", hex(color.to_rgb()))),
+            },
+            None if reverse && self.classes => classes.push(format!("ansi-{layer}-inverse")),
+            None if reverse => {
+                let (fg, bg) = self.theme.colors();
+                styles.push(format!("{property}:{}", hex(if background { fg } else { bg })));
+            },
+            None => (),
+        }
+    }
+}
+
+/// Converts text containing ansi escape sequences into HTML with inline
+/// styles, see [`Html`] for the other options.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::html::to_html;
+/// 
+/// assert_eq!(to_html("\x1b[4mx & y\x1b[0m"), r#"<span style="text-decoration-line:underline">x &amp; y</span>"#);
+/// ```
+pub fn to_html(s: &str) -> String {
+    Html::new().convert(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markup_keeps_colours_and_links() {
+        let output = Html::new().convert_markup("[#ff8800]x[/] .(#ff8800)y_X");
+        assert_eq!(output, r#"<span style="color:#ff8800">x</span> <span style="color:#ff8800">y</span>"#);
+
+        let output = Html::new().convert_markup("see [link=https://example.com/?a=1&b=2]the [bold]docs[/][/link]");
+        assert_eq!(
+            output,
+            r#"see <a href="https://example.com/?a=1&amp;b=2">the <span style="font-weight:bold">docs</span></a>"#,
+        );
+    }
+
+    #[test]
+    fn links_split_spans() {
+        let output = to_html("\x1b[31mred \x1b]8;;https://a.example\x07link\x1b[0m plain\x1b]8;;\x07 after");
+        assert_eq!(
+            output,
+            r#"<span style="color:#cd0000">red </span><a href="https://a.example"><span style="color:#cd0000">link</span> plain</a> after"#,
+        );
+
+        let output = to_html("\x1b]8;;https://a.example\x1b\\a\x1b]8;;https://b.example\x1b\\b");
+        assert_eq!(output, r#"<a href="https://a.example">a</a><a href="https://b.example">b</a>"#);
+    }
+
+    #[test]
+    fn unsafe_links_are_text() {
+        let output = to_html("\x1b]8;;javascript:alert(1)\x07click\x1b]8;;\x07 \x1b]8;;JavaScript:alert(1)\x07me\x1b]8;;\x07");
+        assert_eq!(output, "click me");
+
+        let output = to_html("\x1b]8;;https://a.example\x07a\x1b]8;;data:text/html,x\x07b\x1b]8;;\x07c");
+        assert_eq!(output, r#"<a href="https://a.example">a</a>bc"#);
+
+        let output = Html::new().convert_markup("[link=mailto:a@b.example]a[/link] [link=file:///tmp/b]b[/link]");
+        assert_eq!(output, r#"<a href="mailto:a@b.example">a</a> <a href="file:///tmp/b">b</a>"#);
+    }
+
+    #[test]
+    fn extended_colours_stay_inline() {
+        let output = Html::new().classes().convert("\x1b[1;38;5;208;48;2;32;32;32mx\x1b[0m\x1b[94my");
+        assert_eq!(
+            output,
+            r#"<span class="ansi-bold" style="color:#ff8700;background-color:#202020">x</span><span class="ansi-fg-bright-blue">y</span>"#,
+        );
+    }
+
+    #[test]
+    fn reversed_default_colours() {
+        assert_eq!(to_html("\x1b[7mx"), r#"<span style="color:#1e1e1e;background-color:#e5e5e5">x</span>"#);
+        assert_eq!(
+            Html::new().classes().convert("\x1b[7mx"),
+            r#"<span class="ansi-fg-inverse ansi-bg-inverse">x</span>"#,
+        );
+        assert_eq!(
+            Html::new().theme(Theme::Light).convert("\x1b[7;32mx"),
+            r#"<span style="color:#ffffff;background-color:#00cd00">x</span>"#,
+        );
+    }
+
+    #[test]
+    fn lines_are_combined() {
+        assert_eq!(
+            to_html("\x1b[21;9;53mx"),
+            r#"<span style="text-decoration-style:double;text-decoration-line:underline line-through overline">x</span>"#,
+        );
+        assert!(Html::new().stylesheet().contains(".ansi-underline.ansi-strikethrough { text-decoration-line: underline line-through; }"));
+    }
+}
@@ -0,0 +1,620 @@
+use std::io::{self, Read};
+#[cfg(unix)]
+use std::os::fd::{AsRawFd, RawFd};
+use std::time::Duration;
+
+/// A key of the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    /// A character, as it was typed, so `A` rather than `a` with shift.
+    Char(char),
+    Enter,
+    Tab,
+    /// Tab with shift.
+    BackTab,
+    Backspace,
+    Esc,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    /// A function key, from 1 to 12.
+    F(u8),
+}
+
+/// The modifier keys held down along with a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub alt: bool,
+    pub ctrl: bool,
+}
+
+impl Modifiers {
+    /// The modifiers of the parameter of an xterm style sequence, which is
+    /// 1 plus 1 for shift, 2 for alt and 4 for ctrl.
+    fn from_param(param: u16) -> Self {
+        let bits = param.saturating_sub(1);
+        Self { shift: bits & 1 != 0, alt: bits & 2 != 0, ctrl: bits & 4 != 0 }
+    }
+}
+
+/// A key press read by [`Keys`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    pub code: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    /// A press of `code` without any modifiers.
+    pub fn new(code: KeyCode) -> Self {
+        Self { code, modifiers: Modifiers::default() }
+    }
+
+    /// Adds shift to the modifiers.
+    pub fn shift(mut self) -> Self {
+        self.modifiers.shift = true;
+        self
+    }
+
+    /// Adds alt to the modifiers.
+    pub fn alt(mut self) -> Self {
+        self.modifiers.alt = true;
+        self
+    }
+
+    /// Adds ctrl to the modifiers.
+    pub fn ctrl(mut self) -> Self {
+        self.modifiers.ctrl = true;
+        self
+    }
+}
+
+/// The result of decoding the start of the input.
+enum Parsed {
+    /// A key, made of this many bytes.
+    Key(KeyEvent, usize),
+    /// This many bytes which aren't a key, such as an unknown sequence.
+    Skip(usize),
+    /// The bytes could be the start of a longer sequence.
+    Incomplete,
+}
+
+/// The key of the final byte of a cursor or function key sequence, such as
+/// `A` of `\x1b[A` or `P` of `\x1bOP`.
+fn sequence_key(b: u8) -> Option<KeyCode> {
+    Some(match b {
+        b'A' => KeyCode::Up,
+        b'B' => KeyCode::Down,
+        b'C' => KeyCode::Right,
+        b'D' => KeyCode::Left,
+        b'H' => KeyCode::Home,
+        b'F' => KeyCode::End,
+        b'P' => KeyCode::F(1),
+        b'Q' => KeyCode::F(2),
+        b'R' => KeyCode::F(3),
+        b'S' => KeyCode::F(4),
+        _ => return None,
+    })
+}
+
+/// The key of the number of a `\x1b[n~` sequence.
+fn tilde_key(n: u16) -> Option<KeyCode> {
+    Some(match n {
+        1 | 7 => KeyCode::Home,
+        2 => KeyCode::Insert,
+        3 => KeyCode::Delete,
+        4 | 8 => KeyCode::End,
+        5 => KeyCode::PageUp,
+        6 => KeyCode::PageDown,
+        11..=15 => KeyCode::F(n as u8 - 10),
+        17..=21 => KeyCode::F(n as u8 - 11),
+        23 | 24 => KeyCode::F(n as u8 - 12),
+        _ => return None,
+    })
+}
+
+/// Decodes the key at the start of `bytes`, which isn't empty. If `end`
+/// is set no more bytes are coming, so an incomplete sequence is decoded as
+/// the keys it is made of.
+fn parse(bytes: &[u8], end: bool) -> Parsed {
+    let key = |code| KeyEvent::new(code);
+
+    let event = match bytes[0] {
+        0x1b => return parse_escape(bytes, end),
+        b'\r' | b'\n' => key(KeyCode::Enter),
+        b'\t' => key(KeyCode::Tab),
+        0x7f | 0x08 => key(KeyCode::Backspace),
+        0x00 => key(KeyCode::Char(' ')).ctrl(),
+        b @ 0x01..=0x1a => key(KeyCode::Char((b'a' + b - 1) as char)).ctrl(),
+        b @ 0x1c..=0x1f => key(KeyCode::Char((b'\\' + b - 0x1c) as char)).ctrl(),
+        b => {
+            let len = match b {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => return Parsed::Skip(1),
+            };
+            if bytes.len() < len {
+                return if end { Parsed::Skip(bytes.len()) } else { Parsed::Incomplete };
+            }
+
+            return match std::str::from_utf8(&bytes[..len]) {
+                Ok(s) => Parsed::Key(key(KeyCode::Char(s.chars().next().unwrap())), len),
+                Err(_) => Parsed::Skip(1),
+            };
+        },
+    };
+
+    Parsed::Key(event, 1)
+}
+
+/// Decodes `bytes` starting with an escape, which is either Esc on its
+/// own, an escape sequence or a key pressed with alt.
+fn parse_escape(bytes: &[u8], end: bool) -> Parsed {
+    let alt = |c| Parsed::Key(KeyEvent::new(KeyCode::Char(c)).alt(), 2);
+
+    match bytes.get(1) {
+        None if end => Parsed::Key(KeyEvent::new(KeyCode::Esc), 1),
+        None => Parsed::Incomplete,
+        Some(b'[') => parse_csi(bytes, end),
+        Some(b'O') => match bytes.get(2).map(|&b| sequence_key(b)) {
+            Some(Some(code)) => Parsed::Key(KeyEvent::new(code), 3),
+            Some(None) => alt('O'),
+            None if end => alt('O'),
+            None => Parsed::Incomplete,
+        },
+        // Any other key with alt, including `\x1b\x1b[A` which some
+        // terminals send for alt with up.
+        Some(_) => match parse(&bytes[1..], end) {
+            Parsed::Key(event, len) => Parsed::Key(event.alt(), len + 1),
+            Parsed::Skip(len) => Parsed::Skip(len + 1),
+            Parsed::Incomplete => Parsed::Incomplete,
+        },
+    }
+}
+
+/// Decodes `bytes` starting with `\x1b[`, a control sequence, such as
+/// `\x1b[1;5A` for ctrl with up.
+fn parse_csi(bytes: &[u8], end: bool) -> Parsed {
+    let body = &bytes[2..];
+
+    // The function keys of the linux console, `\x1b[[A` to `\x1b[[E`.
+    if body.first() == Some(&b'[') {
+        return match body.get(1) {
+            Some(&b @ b'A'..=b'E') => Parsed::Key(KeyEvent::new(KeyCode::F(b - b'A' + 1)), 4),
+            Some(_) => Parsed::Skip(4),
+            None if end => Parsed::Skip(3),
+            None => Parsed::Incomplete,
+        };
+    }
+
+    let Some(at) = body.iter().position(|b| !(0x20..=0x3f).contains(b)) else {
+        return match end {
+            false => Parsed::Incomplete,
+            true if body.is_empty() => Parsed::Key(KeyEvent::new(KeyCode::Char('[')).alt(), 2),
+            true => Parsed::Skip(bytes.len()),
+        };
+    };
+    if !(0x40..=0x7e).contains(&body[at]) {
+        // Not a sequence after all, but alt with `[` and then other keys.
+        return Parsed::Key(KeyEvent::new(KeyCode::Char('[')).alt(), 2);
+    }
+
+    let len = at + 3;
+    let params: Vec<Option<u16>> = std::str::from_utf8(&body[..at])
+        .unwrap_or_default()
+        .split(';')
+        .map(|param| param.parse().ok())
+        .collect();
+    let param = |i: usize| params.get(i).copied().flatten();
+
+    let code = match body[at] {
+        b'~' => param(0).and_then(tilde_key),
+        b'Z' => Some(KeyCode::BackTab),
+        b => sequence_key(b),
+    };
+
+    match code {
+        Some(code) => {
+            let modifiers = Modifiers::from_param(param(1).unwrap_or(1));
+            Parsed::Key(KeyEvent { code, modifiers }, len)
+        },
+        None => Parsed::Skip(len),
+    }
+}
+
+/// Iterator of the keys pressed, decoded from the bytes of a reader such
+/// as the stdin. The terminal should be in raw or cbreak mode, see
+/// [`super::terminal::ModeGuard`], so that keys are read as soon as they're
+/// pressed.
+/// 
+/// Escape starts both the sequences of keys such as the arrows and a press
+/// of Esc on its own, so an escape is only read as Esc if nothing follows
+/// it within the timeout. Only terminals can be waited on, see
+/// [`Keys::terminal`], any other reader is read until the next bytes or its
+/// end.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::input::{KeyCode, KeyEvent, Keys};
+/// 
+/// let input = "a\x1b[A\x1b[1;5C\x1b[6~\x1bOP\x1b[15~\x1bxé\x03\x1b".as_bytes();
+/// let keys = Keys::new(input).collect::<std::io::Result<Vec<_>>>()?;
+/// assert_eq!(keys, [
+///     KeyEvent::new(KeyCode::Char('a')),
+///     KeyEvent::new(KeyCode::Up),
+///     KeyEvent::new(KeyCode::Right).ctrl(),
+///     KeyEvent::new(KeyCode::PageDown),
+///     KeyEvent::new(KeyCode::F(1)),
+///     KeyEvent::new(KeyCode::F(5)),
+///     KeyEvent::new(KeyCode::Char('x')).alt(),
+///     KeyEvent::new(KeyCode::Char('é')),
+///     KeyEvent::new(KeyCode::Char('c')).ctrl(),
+///     KeyEvent::new(KeyCode::Esc),
+/// ]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+/// Reading keys until `q` is pressed:
+/// ```no_run
+/// use utils::ansi::{input::{KeyCode, Keys}, terminal};
+/// 
+/// let _guard = terminal::raw_mode()?;
+/// for key in Keys::stdin() {
+///     let key = key?;
+///     if key.code == KeyCode::Char('q') {
+///         break;
+///     }
+///     print!("{key:?}\r\n");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Keys<R> {
+    reader: R,
+    /// The terminal read from directly, so that it can be waited on.
+    #[cfg(unix)]
+    fd: Option<RawFd>,
+    /// The bytes read which haven't been decoded yet.
+    pending: Vec<u8>,
+    timeout: Duration,
+    /// Whether the reader has ended.
+    eof: bool,
+}
+
+impl<R: Read> Keys<R> {
+    /// Reads keys from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            #[cfg(unix)]
+            fd: None,
+            pending: Vec::new(),
+            timeout: Duration::from_millis(50),
+            eof: false,
+        }
+    }
+
+    /// Sets how long to wait after an escape for the rest of a sequence
+    /// before reading it as Esc, 50ms by default. Terminals send the whole
+    /// sequence at once, but it can take longer over a slow connection.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Reads the next bytes into `pending`.
+    fn fill(&mut self) -> io::Result<()> {
+        let mut buf = [0u8; 1024];
+
+        loop {
+            #[cfg(unix)]
+            let read = match self.fd {
+                Some(fd) => {
+                    // SAFETY: `buf` is valid for writes of its whole length.
+                    match unsafe { libc::read(fd, buf.as_mut_ptr().cast(), buf.len()) } {
+                        -1 => Err(io::Error::last_os_error()),
+                        read => Ok(read as usize),
+                    }
+                },
+                None => self.reader.read(&mut buf),
+            };
+            #[cfg(not(unix))]
+            let read = self.reader.read(&mut buf);
+
+            match read {
+                Ok(0) => self.eof = true,
+                Ok(read) => self.pending.extend_from_slice(&buf[..read]),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+            return Ok(());
+        }
+    }
+
+    /// Waits up to the timeout for more bytes, returning whether there
+    /// are any. Readers which aren't terminals are always read from.
+    fn wait(&self) -> io::Result<bool> {
+        #[cfg(unix)]
+        if let Some(fd) = self.fd {
+            let deadline = std::time::Instant::now() + self.timeout;
+
+            loop {
+                let mut poll = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                let millis = remaining.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+
+                // SAFETY: `poll` is a single valid `pollfd`.
+                return match unsafe { libc::poll(&mut poll, 1, millis) } {
+                    // Interrupted by a signal, such as `SIGWINCH` when the
+                    // terminal is resized, so waits for the rest of the time.
+                    -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+                    -1 => Err(io::Error::last_os_error()),
+                    ready => Ok(ready > 0),
+                };
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(unix)]
+impl<R: Read + AsRawFd> Keys<R> {
+    /// Reads keys from the terminal `reader`, waiting up to the timeout
+    /// after an escape for the rest of a sequence.
+    /// 
+    /// ---
+    /// ## Examples
+    /// Reading the keys typed in the terminal the program runs in, even
+    /// when its stdin is redirected:
+    /// ```no_run
+    /// use std::fs::File;
+    /// use utils::ansi::{input::{KeyCode, Keys}, terminal::{Mode, ModeGuard}};
+    /// 
+    /// let tty = File::open("/dev/tty")?;
+    /// let _guard = ModeGuard::new(&tty, Mode::Raw)?;
+    /// for key in Keys::terminal(tty) {
+    ///     if key?.code == KeyCode::Esc {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn terminal(reader: R) -> Self {
+        let fd = reader.as_raw_fd();
+        Self { fd: Some(fd), ..Self::new(reader) }
+    }
+}
+
+impl Keys<io::Stdin> {
+    /// Reads keys from the stdin, see [`Keys::terminal`].
+    pub fn stdin() -> Self {
+        #[cfg(unix)]
+        {
+            Self::terminal(io::stdin())
+        }
+        #[cfg(not(unix))]
+        {
+            Self::new(io::stdin())
+        }
+    }
+}
+
+impl<R: Read> Iterator for Keys<R> {
+    type Item = io::Result<KeyEvent>;
+
+    fn next(&mut self) -> Option<io::Result<KeyEvent>> {
+        // Whether nothing followed the pending bytes within the timeout.
+        let mut timed_out = false;
+
+        loop {
+            if self.pending.is_empty() && self.eof {
+                return None;
+            }
+
+            if !self.pending.is_empty() {
+                match parse(&self.pending, timed_out || self.eof) {
+                    Parsed::Key(event, len) => {
+                        self.pending.drain(..len);
+                        return Some(Ok(event));
+                    },
+                    Parsed::Skip(len) => {
+                        self.pending.drain(..len);
+                        continue;
+                    },
+                    Parsed::Incomplete => match self.wait() {
+                        Ok(true) => (),
+                        Ok(false) => {
+                            timed_out = true;
+                            continue;
+                        },
+                        Err(err) => return Some(Err(err)),
+                    },
+                }
+            }
+
+            if let Err(err) = self.fill() {
+                return Some(Err(err));
+            }
+            timed_out = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A reader giving one byte at a time, like a slow connection.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let Some((&first, rest)) = self.0.split_first() else {
+                return Ok(0);
+            };
+            buf[0] = first;
+            self.0 = rest;
+            Ok(1)
+        }
+    }
+
+    fn keys(reader: impl Read) -> Vec<KeyEvent> {
+        Keys::new(reader).collect::<io::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn sequences_split_across_reads() {
+        let input = "\x1b[1;5A\x1bOQ\x1b[[Bé\x1b[3~";
+        let expected = [
+            KeyEvent::new(KeyCode::Up).ctrl(),
+            KeyEvent::new(KeyCode::F(2)),
+            KeyEvent::new(KeyCode::F(2)),
+            KeyEvent::new(KeyCode::Char('é')),
+            KeyEvent::new(KeyCode::Delete),
+        ];
+        assert_eq!(keys(input.as_bytes()), expected);
+        assert_eq!(keys(Trickle(input.as_bytes())), expected);
+    }
+
+    #[test]
+    fn modifiers_and_alt() {
+        assert_eq!(keys(&b"\x1b[3;3~\x1b[1;2Z\x1b[Z\x1b[1;8H"[..]), [
+            KeyEvent::new(KeyCode::Delete).alt(),
+            KeyEvent::new(KeyCode::BackTab).shift(),
+            KeyEvent::new(KeyCode::BackTab),
+            KeyEvent::new(KeyCode::Home).shift().alt().ctrl(),
+        ]);
+        assert_eq!(keys(&b"\x1b\x1b[A\x1b\r\x1b\x01\x1b\x1b"[..]), [
+            KeyEvent::new(KeyCode::Up).alt(),
+            KeyEvent::new(KeyCode::Enter).alt(),
+            KeyEvent::new(KeyCode::Char('a')).ctrl().alt(),
+            KeyEvent::new(KeyCode::Esc).alt(),
+        ]);
+    }
+
+    #[test]
+    fn control_keys() {
+        assert_eq!(keys(&b"\x00\x1c\x1f\t\n\x08\x7f"[..]), [
+            KeyEvent::new(KeyCode::Char(' ')).ctrl(),
+            KeyEvent::new(KeyCode::Char('\\')).ctrl(),
+            KeyEvent::new(KeyCode::Char('_')).ctrl(),
+            KeyEvent::new(KeyCode::Tab),
+            KeyEvent::new(KeyCode::Enter),
+            KeyEvent::new(KeyCode::Backspace),
+            KeyEvent::new(KeyCode::Backspace),
+        ]);
+    }
+
+    #[test]
+    fn unknown_sequences_are_skipped() {
+        assert_eq!(keys(&b"\x1b[99~a\x1b[2Jb\x1b[[Zc\x1b[?1;2cd"[..]), [
+            KeyEvent::new(KeyCode::Char('a')),
+            KeyEvent::new(KeyCode::Char('b')),
+            KeyEvent::new(KeyCode::Char('c')),
+            KeyEvent::new(KeyCode::Char('d')),
+        ]);
+    }
+
+    #[test]
+    fn not_a_sequence() {
+        assert_eq!(keys(&b"\x1b[\x01\x1bOx"[..]), [
+            KeyEvent::new(KeyCode::Char('[')).alt(),
+            KeyEvent::new(KeyCode::Char('a')).ctrl(),
+            KeyEvent::new(KeyCode::Char('O')).alt(),
+            KeyEvent::new(KeyCode::Char('x')),
+        ]);
+    }
+
+    #[test]
+    fn truncated_at_the_end() {
+        assert_eq!(keys(&b"\x1b["[..]), [KeyEvent::new(KeyCode::Char('[')).alt()]);
+        assert_eq!(keys(&b"\x1bO"[..]), [KeyEvent::new(KeyCode::Char('O')).alt()]);
+        assert_eq!(keys(&b"a\x1b[1;5"[..]), [KeyEvent::new(KeyCode::Char('a'))]);
+        assert_eq!(keys(&b"a\x1b[["[..]), [KeyEvent::new(KeyCode::Char('a'))]);
+        assert_eq!(keys(&b"a\xe6\x97"[..]), [KeyEvent::new(KeyCode::Char('a'))]);
+    }
+
+    #[test]
+    fn invalid_utf8_is_skipped() {
+        assert_eq!(keys(&b"\xffa\xc3b\x80\xe6\x97\xa5"[..]), [
+            KeyEvent::new(KeyCode::Char('a')),
+            KeyEvent::new(KeyCode::Char('b')),
+            KeyEvent::new(KeyCode::Char('日')),
+        ]);
+    }
+
+    /// Keys read from a pseudo terminal, which can be waited on.
+    #[cfg(unix)]
+    mod terminal {
+        use super::*;
+        use std::io::Write;
+        use std::thread;
+
+        use crate::ansi::terminal::testing::{alone, pty, run_alone};
+        use crate::ansi::terminal::{Mode, ModeGuard};
+
+        extern "C" fn ignore(_: libc::c_int) {}
+
+        #[test]
+        fn escape_timeout() {
+            if !alone() {
+                let output = run_alone(module_path!(), "escape_timeout");
+                assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+                return;
+            }
+
+            let (mut primary, secondary) = pty();
+            let _guard = ModeGuard::new(&secondary, Mode::Raw).unwrap();
+            let mut keys = Keys::terminal(secondary);
+
+            primary.write_all(b"\x1b").unwrap();
+            assert_eq!(keys.next().unwrap().unwrap(), KeyEvent::new(KeyCode::Esc));
+
+            primary.write_all(b"\x1b[1;2B").unwrap();
+            assert_eq!(keys.next().unwrap().unwrap(), KeyEvent::new(KeyCode::Down).shift());
+        }
+
+        #[test]
+        fn escape_timeout_interrupted_by_signal() {
+            if !alone() {
+                let output = run_alone(module_path!(), "escape_timeout_interrupted_by_signal");
+                assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+                return;
+            }
+
+            let (mut primary, secondary) = pty();
+            let _guard = ModeGuard::new(&secondary, Mode::Raw).unwrap();
+            let mut keys = Keys::terminal(secondary).timeout(Duration::from_millis(200));
+
+            let ignore: extern "C" fn(libc::c_int) = ignore;
+            unsafe { libc::signal(libc::SIGUSR1, ignore as libc::sighandler_t) };
+            let thread = unsafe { libc::pthread_self() };
+
+            primary.write_all(b"\x1b").unwrap();
+            let mut writer = primary.try_clone().unwrap();
+            let signaller = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                unsafe { libc::pthread_kill(thread, libc::SIGUSR1) };
+                // Read as Alt+x if the escape were still waiting.
+                thread::sleep(Duration::from_millis(1000));
+                writer.write_all(b"x").unwrap();
+            });
+
+            assert_eq!(keys.next().unwrap().unwrap(), KeyEvent::new(KeyCode::Esc));
+            assert_eq!(keys.next().unwrap().unwrap(), KeyEvent::new(KeyCode::Char('x')));
+
+            signaller.join().unwrap();
+        }
+    }
+}
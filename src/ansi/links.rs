@@ -0,0 +1,127 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use super::policy::{self, Stream};
+use super::sequences::split;
+use super::tokens::{write_url, Token};
+
+/// Writes `text` as an OSC 8 hyperlink to `url`, which terminals which
+/// support them let users click. If hyperlinks are disabled by
+/// [`policy::hyperlinks`] it is written as `text (url)` instead. The same
+/// links can be written in markup as `[link=url]text[/link]`.
+/// 
+/// Control characters in `url` are percent encoded, and left out of `text`
+/// along with any escape sequences other than colours and styles, so
+/// neither can end the link early or move the cursor.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{hyperlink, strip_ansi, policy::{self, ColorMode}};
+/// 
+/// policy::set_mode(ColorMode::Always);
+/// policy::set_hyperlinks(Some(true));
+/// 
+/// let link = hyperlink("https://example.com", "example");
+/// assert_eq!(link, "\x1b]8;;https://example.com\x1b\\example\x1b]8;;\x1b\\");
+/// assert_eq!(strip_ansi(&link), "example");
+/// 
+/// let link = hyperlink("https://example.com/\x1b[2J", "\x1b[32mex\x07ample\x1b[0m");
+/// assert_eq!(link, "\x1b]8;;https://example.com/%1B[2J\x1b\\\x1b[32mexample\x1b[0m\x1b]8;;\x1b\\");
+/// ```
+pub fn hyperlink(url: &str, text: &str) -> String {
+    let text = clean_text(text);
+    let mut out = String::with_capacity(url.len() + text.len() + 12);
+
+    if policy::hyperlinks(Stream::Stdout) {
+        write!(out, "{}{text}{}", Token::Link(url), Token::LinkEnd(url))
+    } else {
+        write!(out, "{text} (").and_then(|_| write_url(url, &mut out)).map(|_| out.push(')'))
+    }
+    .expect("writing to a String cannot fail");
+
+    out
+}
+
+/// `text` without control characters or escape sequences, other than the
+/// SGR sequences setting colours and styles.
+fn clean_text(text: &str) -> String {
+    let sgr = |part: &str| {
+        part.strip_prefix("\x1b[")
+            .and_then(|part| part.strip_suffix('m'))
+            .is_some_and(|params| params.bytes().all(|b| b.is_ascii_digit() || b == b';' || b == b':'))
+    };
+
+    let mut out = String::with_capacity(text.len());
+    for (escape, part) in split(text) {
+        if !escape {
+            out.extend(part.chars().filter(|c| !c.is_control()));
+        } else if sgr(part) {
+            out.push_str(part);
+        }
+    }
+
+    out
+}
+
+/// The `file://` url of `path`, made absolute and with the hostname of the
+/// machine filled in, as terminals need the hostname to open the file.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{file_url, hyperlink};
+/// 
+/// let url = file_url("/var/log/build output.txt");
+/// assert!(url.starts_with("file://"));
+/// assert!(url.ends_with("/var/log/build%20output.txt"));
+/// 
+/// println!("Saved to {}", hyperlink(&url, "build output.txt"));
+/// ```
+pub fn file_url(path: impl AsRef<Path>) -> String {
+    let path = path.as_ref();
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+
+    #[cfg(unix)]
+    let bytes = path.as_os_str().as_encoded_bytes();
+    #[cfg(not(unix))]
+    let path = path.to_string_lossy().replace('\\', "/");
+    #[cfg(not(unix))]
+    let path = if path.starts_with('/') { path } else { format!("/{path}") };
+    #[cfg(not(unix))]
+    let bytes = path.as_bytes();
+
+    let mut url = format!("file://{}", hostname());
+    for &b in bytes {
+        if b.is_ascii_alphanumeric() || b"-._~/:@!$&'()*+,;=".contains(&b) {
+            url.push(b as char);
+        } else {
+            url.push_str(&format!("%{b:02X}"));
+        }
+    }
+
+    url
+}
+
+/// The hostname of the machine, or an empty string if it can't be found.
+fn hostname() -> &'static str {
+    static HOSTNAME: OnceLock<String> = OnceLock::new();
+
+    HOSTNAME.get_or_init(|| {
+        #[cfg(unix)]
+        {
+            let mut buf = [0u8; 256];
+            // SAFETY: `buf` is valid for writes of its whole length.
+            if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+                return String::new();
+            }
+            let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            String::from_utf8_lossy(&buf[..len]).into_owned()
+        }
+        #[cfg(not(unix))]
+        {
+            std::env::var("COMPUTERNAME").unwrap_or_default()
+        }
+    })
+}
@@ -0,0 +1,135 @@
+use std::fmt;
+
+use super::policy::{self, ColorDepth, Stream};
+use super::tags::Markup;
+use super::tokens::{write_url, Token};
+
+/// Function which replaces the tags and custom ansi mapping strings with
+/// their respective ansi escape codes. Like [`super::replace_cc`] the codes
+/// are written whether or not colour is enabled, but colours the terminal
+/// can't show are turned into the closest colour it can, see
+/// [`policy::depth`], and links are written as `text (url)` where
+/// [`policy::hyperlinks`] says hyperlinks aren't supported, like
+/// [`super::hyperlink`].
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::markup::render;
+/// 
+/// let output = render("[bold on blue]Error in [red]main.rs[/red] at line 3[/]");
+/// assert_eq!(
+///     output,
+///     "\x1b[1m\x1b[44mError in \x1b[31mmain.rs\x1b[0m\x1b[1m\x1b[44m at line 3\x1b[0m",
+/// );
+/// ```
+/// RGB colours at each depth:
+/// ```
+/// use utils::ansi::{markup::render, policy::{self, ColorDepth}};
+/// 
+/// policy::set_depth(Some(ColorDepth::TrueColor));
+/// assert_eq!(render("[#ff8800]hot[/]"), "\x1b[38;2;255;136;0mhot\x1b[0m");
+/// 
+/// policy::set_depth(Some(ColorDepth::Ansi256));
+/// assert_eq!(render("[#ff8800]hot[/]"), "\x1b[38;5;208mhot\x1b[0m");
+/// ```
+/// Tags can be mixed with the other codes, which are turned back on too:
+/// ```
+/// use utils::ansi::markup::render;
+/// 
+/// let output = render("_BLDWarning: [yellow]disk full[/] again_X");
+/// assert_eq!(output, "\x1b[1mWarning: \x1b[33mdisk full\x1b[0m\x1b[1m again\x1b[0m");
+/// ```
+/// Text in brackets which isn't a tag is left as it is, but any which is
+/// a tag, like `[default]`, is read as one and has to be escaped with a
+/// backslash to be printed as is:
+/// ```
+/// use utils::ansi::markup::render;
+/// 
+/// assert_eq!(render(r"[INFO] \[red] is a tag"), "[INFO] [red] is a tag");
+/// assert_eq!(render("[default] options [/]"), "\x1b[39m options \x1b[0m");
+/// assert_eq!(render(r"\[default] options \[/]"), "[default] options [/]");
+/// ```
+/// Links, with hyperlinks enabled and disabled:
+/// ```
+/// use utils::ansi::{markup::render, policy::{self, ColorMode}};
+/// 
+/// policy::set_mode(ColorMode::Always);
+/// policy::set_hyperlinks(Some(true));
+/// assert_eq!(
+///     render("[link=https://example.com]docs[/link]"),
+///     "\x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\",
+/// );
+/// 
+/// policy::set_hyperlinks(Some(false));
+/// assert_eq!(render("[link=https://example.com]docs[/link]"), "docs (https://example.com)");
+/// ```
+pub fn render(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+
+    render_into(s, &mut out).expect("writing to a String cannot fail");
+
+    out
+}
+
+/// Non-allocating version of [`render`] which writes the string, with its
+/// tags and codes replaced, into any [`fmt::Write`].
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::markup::render_into;
+/// 
+/// let mut output = String::new();
+/// render_into("[green]ok[/green]", &mut output).unwrap();
+/// assert_eq!(output, "\x1b[32mok\x1b[0m");
+/// ```
+pub fn render_into(s: &str, w: &mut impl fmt::Write) -> fmt::Result {
+    render_at(s, policy::depth(), policy::hyperlinks(Stream::Stdout), w)
+}
+
+/// Version of [`render_into`] for a terminal which can show `depth` colours
+/// and, if `links`, hyperlinks, whatever the terminal this runs in.
+pub(crate) fn render_at(s: &str, depth: ColorDepth, links: bool, w: &mut impl fmt::Write) -> fmt::Result {
+    let mut result = Ok(());
+    let mut write = |token: Token| {
+        if result.is_ok() {
+            result = match token {
+                Token::Link(_) if !links => Ok(()),
+                Token::LinkEnd(url) if !links => {
+                    w.write_str(" (").and_then(|_| write_url(url, w)).and_then(|_| w.write_str(")"))
+                },
+                token => token.write_at(depth, w),
+            };
+        }
+    };
+
+    let mut markup = Markup::new();
+    markup.feed(s, &mut write);
+    markup.finish(&mut write);
+
+    result
+}
+
+/// Function which removes the tags and custom ansi mapping strings, leaving
+/// only the text.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::markup::strip;
+/// 
+/// assert_eq!(strip("[bold on blue]Error in [red]main.rs[/red][/]"), "Error in main.rs");
+/// ```
+pub fn strip(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut markup = Markup::new();
+
+    markup.feed(s, |token| {
+        if let Token::Text(text) = token {
+            out.push_str(text);
+        }
+    });
+
+    out
+}
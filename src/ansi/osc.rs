@@ -0,0 +1,211 @@
+use std::fmt;
+use std::io;
+
+/// Ends an operating system command.
+const ST: &str = "\x1b\\";
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes `bytes` as padded base64.
+fn base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+/// `s` without the control characters which would end the command early.
+fn clean(s: &str) -> String {
+    s.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Sets the title of the terminal window and tab, `OSC 0`.
+/// 
+/// ---
+/// ## Examples
+/// Showing the progress of a long running job:
+/// ```
+/// use utils::ansi::osc::Title;
+/// 
+/// let mut out = Vec::new();
+/// Title::new("build: 45%").write_to(&mut out).unwrap();
+/// assert_eq!(out, b"\x1b]0;build: 45%\x1b\\");
+/// 
+/// assert_eq!(Title::new("build").window().to_string(), "\x1b]2;build\x1b\\");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Title {
+    text: String,
+    /// 0 for both, 1 for the icon (the tab in most terminals), 2 for the
+    /// window.
+    kind: u8,
+}
+
+impl Title {
+    /// Sets both the window and the tab title to `text`. Control
+    /// characters in it are left out.
+    pub fn new(text: impl AsRef<str>) -> Self {
+        Self { text: clean(text.as_ref()), kind: 0 }
+    }
+
+    /// Sets only the title of the tab, or of the icon of the window in
+    /// terminals without tabs, `OSC 1`.
+    pub fn tab(mut self) -> Self {
+        self.kind = 1;
+        self
+    }
+
+    /// Sets only the title of the window, `OSC 2`.
+    pub fn window(mut self) -> Self {
+        self.kind = 2;
+        self
+    }
+
+    /// Writes the escape sequence to `w`.
+    pub fn write_to(&self, w: &mut impl io::Write) -> io::Result<()> {
+        write!(w, "{self}")
+    }
+}
+
+impl fmt::Display for Title {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b]{};{}{ST}", self.kind, self.text)
+    }
+}
+
+/// Copies to the clipboard through the terminal, `OSC 52`, which works
+/// over ssh too. The data is sent base64 encoded.
+/// 
+/// *Note: Some terminals turn this off by default or limit the size of the
+/// data, xterm for one takes about 74KB at most.*
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::osc::Clipboard;
+/// 
+/// let mut out = Vec::new();
+/// Clipboard::new("token-1234").write_to(&mut out).unwrap();
+/// assert_eq!(out, b"\x1b]52;c;dG9rZW4tMTIzNA==\x1b\\");
+/// 
+/// assert_eq!(Clipboard::new("hi").primary().to_string(), "\x1b]52;p;aGk=\x1b\\");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Clipboard {
+    /// The data, base64 encoded.
+    data: String,
+    /// `c` for the clipboard, `p` for the primary selection.
+    selection: char,
+}
+
+impl Clipboard {
+    /// Copies `data` to the clipboard.
+    pub fn new(data: impl AsRef<[u8]>) -> Self {
+        Self { data: base64(data.as_ref()), selection: 'c' }
+    }
+
+    /// Copies to the primary selection instead, which is pasted with the
+    /// middle mouse button on X11 and Wayland.
+    pub fn primary(mut self) -> Self {
+        self.selection = 'p';
+        self
+    }
+
+    /// Writes the escape sequence to `w`.
+    pub fn write_to(&self, w: &mut impl io::Write) -> io::Result<()> {
+        write!(w, "{self}")
+    }
+}
+
+impl fmt::Display for Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b]52;{};{}{ST}", self.selection, self.data)
+    }
+}
+
+/// The escape sequence a [`Notification`] is sent with, as terminals
+/// understand one or the other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Protocol {
+    /// `OSC 9`, understood by iTerm2, Windows Terminal, kitty, WezTerm and
+    /// Ghostty. It has no title, so the title is put before the body.
+    #[default]
+    Osc9,
+    /// `OSC 777`, understood by urxvt, foot, Ghostty, WezTerm and VTE based
+    /// terminals such as GNOME Terminal.
+    Osc777,
+}
+
+/// Sends a desktop notification through the terminal.
+/// 
+/// ---
+/// ## Examples
+/// Telling the user a long running job is done:
+/// ```
+/// use utils::ansi::osc::{Notification, Protocol};
+/// 
+/// let mut out = Vec::new();
+/// Notification::new("Build finished").write_to(&mut out).unwrap();
+/// assert_eq!(out, b"\x1b]9;Build finished\x1b\\");
+/// 
+/// let notification = Notification::new("42 tests passed").title("cargo test");
+/// assert_eq!(notification.to_string(), "\x1b]9;cargo test: 42 tests passed\x1b\\");
+/// assert_eq!(
+///     notification.protocol(Protocol::Osc777).to_string(),
+///     "\x1b]777;notify;cargo test;42 tests passed\x1b\\",
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Notification {
+    title: Option<String>,
+    body: String,
+    protocol: Protocol,
+}
+
+impl Notification {
+    /// A notification of `body`, sent with [`Protocol::Osc9`]. Control
+    /// characters in it are left out.
+    pub fn new(body: impl AsRef<str>) -> Self {
+        Self { title: None, body: clean(body.as_ref()), protocol: Protocol::default() }
+    }
+
+    /// Sets the title of the notification. Control characters and `;`,
+    /// which would end the title early, are left out.
+    pub fn title(mut self, title: impl AsRef<str>) -> Self {
+        self.title = Some(clean(title.as_ref()).replace(';', ""));
+        self
+    }
+
+    /// Sets the escape sequence the notification is sent with.
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Writes the escape sequence to `w`.
+    pub fn write_to(&self, w: &mut impl io::Write) -> io::Result<()> {
+        write!(w, "{self}")
+    }
+}
+
+impl fmt::Display for Notification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.protocol, &self.title) {
+            (Protocol::Osc9, Some(title)) => write!(f, "\x1b]9;{title}: {}{ST}", self.body),
+            (Protocol::Osc9, None) => write!(f, "\x1b]9;{}{ST}", self.body),
+            (Protocol::Osc777, title) => {
+                write!(f, "\x1b]777;notify;{};{}{ST}", title.as_deref().unwrap_or_default(), self.body)
+            },
+        }
+    }
+}
@@ -0,0 +1,240 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+pub use super::palette::ColorDepth;
+
+/// When to write ansi escape codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Detect from the environment and whether the stream is a terminal.
+    Auto,
+    /// Always write escape codes.
+    Always,
+    /// Never write escape codes.
+    Never,
+}
+
+/// The stream output is written to, which can be a terminal on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+static MODE: AtomicU8 = AtomicU8::new(ColorMode::Auto as u8);
+
+/// Sets the [`ColorMode`] for the whole process, overriding detection.
+/// Setting it back to [`ColorMode::Auto`] goes back to detection.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::policy::{self, ColorMode, Stream};
+/// 
+/// policy::set_mode(ColorMode::Never);
+/// assert!(!policy::enabled(Stream::Stdout));
+/// 
+/// policy::set_mode(ColorMode::Always);
+/// assert!(policy::enabled(Stream::Stderr));
+/// ```
+pub fn set_mode(mode: ColorMode) {
+    MODE.store(mode as u8, Ordering::Relaxed);
+}
+
+/// The current [`ColorMode`], [`ColorMode::Auto`] unless set by
+/// [`set_mode`].
+pub fn mode() -> ColorMode {
+    match MODE.load(Ordering::Relaxed) {
+        m if m == ColorMode::Always as u8 => ColorMode::Always,
+        m if m == ColorMode::Never as u8 => ColorMode::Never,
+        _ => ColorMode::Auto,
+    }
+}
+
+/// Whether escape codes should be written to `stream` under the current
+/// [`ColorMode`].
+/// 
+/// With [`ColorMode::Auto`], in order:
+/// 1. `NO_COLOR` set to anything but an empty string disables colour.
+/// 2. `CLICOLOR_FORCE` set to anything but `0` enables colour.
+/// 3. `CLICOLOR=0` disables colour.
+/// 4. `TERM=dumb` disables colour.
+/// 5. Otherwise colour is enabled only if `stream` is a terminal.
+/// 
+/// *Note: The environment is only read the first time each stream is
+/// checked, use [`set_mode`] to change it later on.*
+pub fn enabled(stream: Stream) -> bool {
+    static STDOUT: OnceLock<bool> = OnceLock::new();
+    static STDERR: OnceLock<bool> = OnceLock::new();
+
+    match (mode(), stream) {
+        (ColorMode::Always, _) => true,
+        (ColorMode::Never, _) => false,
+        (ColorMode::Auto, Stream::Stdout) => {
+            *STDOUT.get_or_init(|| detect(std::io::stdout().is_terminal()))
+        },
+        (ColorMode::Auto, Stream::Stderr) => {
+            *STDERR.get_or_init(|| detect(std::io::stderr().is_terminal()))
+        },
+    }
+}
+
+fn detect(is_terminal: bool) -> bool {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty());
+
+    if var("NO_COLOR").is_some() {
+        return false;
+    }
+    if var("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+        return true;
+    }
+    if var("CLICOLOR").is_some_and(|v| v == "0") {
+        return false;
+    }
+    if var("TERM").is_some_and(|v| v == "dumb") {
+        return false;
+    }
+
+    is_terminal
+}
+
+/// Returns `code` if escape codes should be written to `stream`, otherwise
+/// an empty string.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{RED, X, policy::{self, ColorMode, Stream}};
+/// 
+/// policy::set_mode(ColorMode::Never);
+/// let output = format!("{}Error{}", policy::paint(RED, Stream::Stdout), policy::paint(X, Stream::Stdout));
+/// assert_eq!(output, "Error");
+/// ```
+pub fn paint(code: &str, stream: Stream) -> &str {
+    if enabled(stream) {
+        code
+    } else {
+        ""
+    }
+}
+
+static DEPTH: AtomicU8 = AtomicU8::new(0);
+
+/// Sets the [`ColorDepth`] for the whole process, overriding detection.
+/// Setting it to [`None`] goes back to detection.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{f_color, policy::{self, ColorDepth, ColorMode}};
+/// 
+/// policy::set_mode(ColorMode::Always);
+/// 
+/// policy::set_depth(Some(ColorDepth::TrueColor));
+/// assert_eq!(f_color::rgb([255, 135, 0]), "\x1b[38;2;255;135;0m");
+/// 
+/// policy::set_depth(Some(ColorDepth::Ansi256));
+/// assert_eq!(f_color::rgb([255, 135, 0]), "\x1b[38;5;208m");
+/// 
+/// policy::set_depth(Some(ColorDepth::Ansi16));
+/// assert_eq!(f_color::rgb([255, 135, 0]), "\x1b[91m");
+/// ```
+pub fn set_depth(depth: Option<ColorDepth>) {
+    DEPTH.store(depth.map_or(0, |depth| depth as u8 + 1), Ordering::Relaxed);
+}
+
+/// The [`ColorDepth`] colours are written with, RGB and 256 colour palette
+/// colours are turned into the closest colour the terminal can show.
+/// 
+/// Unless set by [`set_depth`] it is detected as:
+/// 1. [`ColorDepth::TrueColor`] if `COLORTERM` is `truecolor` or `24bit`,
+///    or `TERM` ends in `-direct`.
+/// 2. [`ColorDepth::Ansi256`] if `TERM` contains `256color`.
+/// 3. [`ColorDepth::TrueColor`] on windows if `TERM` is not set.
+/// 4. [`ColorDepth::Ansi16`] otherwise.
+/// 
+/// *Note: The environment is only read the first time this is called.*
+pub fn depth() -> ColorDepth {
+    static DETECTED: OnceLock<ColorDepth> = OnceLock::new();
+
+    match DEPTH.load(Ordering::Relaxed) {
+        1 => ColorDepth::Ansi16,
+        2 => ColorDepth::Ansi256,
+        3 => ColorDepth::TrueColor,
+        _ => *DETECTED.get_or_init(detect_depth),
+    }
+}
+
+fn detect_depth() -> ColorDepth {
+    let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+    let term = std::env::var("TERM").ok();
+
+    match term.as_deref() {
+        _ if colorterm == "truecolor" || colorterm == "24bit" => ColorDepth::TrueColor,
+        Some(term) if term.ends_with("-direct") => ColorDepth::TrueColor,
+        Some(term) if term.contains("256color") => ColorDepth::Ansi256,
+        None if cfg!(windows) => ColorDepth::TrueColor,
+        _ => ColorDepth::Ansi16,
+    }
+}
+
+static HYPERLINKS: AtomicU8 = AtomicU8::new(0);
+
+/// Sets whether hyperlinks are written for the whole process, overriding
+/// detection. Setting it to [`None`] goes back to detection.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{hyperlink, policy::{self, ColorMode}};
+/// 
+/// policy::set_mode(ColorMode::Always);
+/// 
+/// policy::set_hyperlinks(Some(false));
+/// assert_eq!(hyperlink("https://example.com", "example"), "example (https://example.com)");
+/// ```
+pub fn set_hyperlinks(hyperlinks: Option<bool>) {
+    HYPERLINKS.store(hyperlinks.map_or(0, |on| on as u8 + 1), Ordering::Relaxed);
+}
+
+/// Whether OSC 8 hyperlinks should be written to `stream`, otherwise links
+/// are written as `text (url)`. Hyperlinks are never written when colour
+/// is disabled for `stream`, see [`enabled`].
+/// 
+/// Unless set by [`set_hyperlinks`] it is detected as:
+/// 1. `FORCE_HYPERLINK` set to `0` disables hyperlinks, and set to anything
+///    else enables them.
+/// 2. Hyperlinks are enabled in terminals known to support them, found by
+///    `TERM_PROGRAM` (iTerm2, WezTerm, VS Code, Ghostty), `VTE_VERSION`
+///    (GNOME Terminal and other VTE terminals), `KONSOLE_VERSION`,
+///    `WT_SESSION` (Windows Terminal) or `TERM` (kitty, foot, Alacritty).
+/// 3. Hyperlinks are disabled otherwise.
+/// 
+/// *Note: The environment is only read the first time this is called.*
+pub fn hyperlinks(stream: Stream) -> bool {
+    static DETECTED: OnceLock<bool> = OnceLock::new();
+
+    enabled(stream) && match HYPERLINKS.load(Ordering::Relaxed) {
+        1 => false,
+        2 => true,
+        _ => *DETECTED.get_or_init(detect_hyperlinks),
+    }
+}
+
+fn detect_hyperlinks() -> bool {
+    let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+
+    if let Some(force) = var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+
+    let term_program = var("TERM_PROGRAM").unwrap_or_default();
+    let term = var("TERM").unwrap_or_default();
+
+    ["iTerm.app", "WezTerm", "vscode", "ghostty"].contains(&term_program.as_str())
+        || var("VTE_VERSION").and_then(|v| v.parse::<u32>().ok()).is_some_and(|v| v >= 5000)
+        || var("KONSOLE_VERSION").is_some()
+        || var("WT_SESSION").is_some()
+        || ["xterm-kitty", "foot", "alacritty"].iter().any(|t| term.starts_with(t))
+}
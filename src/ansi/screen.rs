@@ -0,0 +1,259 @@
+use std::fmt;
+use std::io;
+
+/// Clears part of the screen, `ED`. The cursor stays where it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Clear {
+    /// The whole screen.
+    All,
+    /// From the cursor to the end of the screen.
+    ToEnd,
+    /// From the start of the screen to the cursor.
+    ToStart,
+    /// The lines scrolled off the top of the screen, in terminals which
+    /// keep them.
+    Scrollback,
+}
+
+impl fmt::Display for Clear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Clear::All => "\x1b[2J",
+            Clear::ToEnd => "\x1b[J",
+            Clear::ToStart => "\x1b[1J",
+            Clear::Scrollback => "\x1b[3J",
+        })
+    }
+}
+
+/// Clears part of the line the cursor is on, `EL`. The cursor stays where
+/// it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClearLine {
+    /// The whole line.
+    All,
+    /// From the cursor to the end of the line.
+    ToEnd,
+    /// From the start of the line to the cursor.
+    ToStart,
+}
+
+impl fmt::Display for ClearLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ClearLine::All => "\x1b[2K",
+            ClearLine::ToEnd => "\x1b[K",
+            ClearLine::ToStart => "\x1b[1K",
+        })
+    }
+}
+
+/// Switches to the alternate screen, which has no scrollback and is
+/// thrown away when left, see [`AlternateScreen`] to have it left
+/// whatever happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EnterAlternateScreen;
+
+impl fmt::Display for EnterAlternateScreen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[?1049h")
+    }
+}
+
+/// Switches back to the main screen after [`EnterAlternateScreen`], as it
+/// was before.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LeaveAlternateScreen;
+
+impl fmt::Display for LeaveAlternateScreen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[?1049l")
+    }
+}
+
+/// Scrolls the text of the screen, or of the scroll region, up a number
+/// of lines, adding blank lines at the bottom, `SU`.
+/// 
+/// Nothing is written for 0, which terminals would read as 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScrollUp(pub usize);
+
+impl fmt::Display for ScrollUp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => Ok(()),
+            n => write!(f, "\x1b[{n}S"),
+        }
+    }
+}
+
+/// Scrolls the text of the screen, or of the scroll region, down a number
+/// of lines, adding blank lines at the top, `SD`.
+/// 
+/// Nothing is written for 0, which terminals would read as 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScrollDown(pub usize);
+
+impl fmt::Display for ScrollDown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => Ok(()),
+            n => write!(f, "\x1b[{n}T"),
+        }
+    }
+}
+
+/// Limits scrolling to the lines from the top to the bottom line, both
+/// starting at 1, `DECSTBM`. Lines outside of it stay in place, such as a
+/// status line at the bottom. The cursor is moved to the home position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScrollRegion(pub usize, pub usize);
+
+impl fmt::Display for ScrollRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\x1b[{};{}r", self.0, self.1)
+    }
+}
+
+/// Scrolls the whole screen again after [`ScrollRegion`]. The cursor is
+/// moved to the home position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResetScrollRegion;
+
+impl fmt::Display for ResetScrollRegion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\x1b[r")
+    }
+}
+
+/// Extension trait for clearing and scrolling the screen by writing to
+/// any [`io::Write`], like [`super::cursor::CursorExt`].
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{cursor::CursorExt, screen::{Clear, ScreenExt}};
+/// 
+/// let mut frame = Vec::new();
+/// frame.clear_screen(Clear::All)?;
+/// frame.move_home()?;
+/// assert_eq!(frame, b"\x1b[2J\x1b[H");
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub trait ScreenExt: io::Write {
+    /// Writes [`Clear`].
+    fn clear_screen(&mut self, clear: Clear) -> io::Result<()> {
+        write!(self, "{clear}")
+    }
+
+    /// Writes [`ClearLine`].
+    fn clear_line(&mut self, clear: ClearLine) -> io::Result<()> {
+        write!(self, "{clear}")
+    }
+
+    /// Writes [`EnterAlternateScreen`].
+    fn enter_alternate_screen(&mut self) -> io::Result<()> {
+        write!(self, "{EnterAlternateScreen}")
+    }
+
+    /// Writes [`LeaveAlternateScreen`].
+    fn leave_alternate_screen(&mut self) -> io::Result<()> {
+        write!(self, "{LeaveAlternateScreen}")
+    }
+
+    /// Writes [`ScrollUp`].
+    fn scroll_up(&mut self, lines: usize) -> io::Result<()> {
+        write!(self, "{}", ScrollUp(lines))
+    }
+
+    /// Writes [`ScrollDown`].
+    fn scroll_down(&mut self, lines: usize) -> io::Result<()> {
+        write!(self, "{}", ScrollDown(lines))
+    }
+
+    /// Writes [`ScrollRegion`].
+    fn set_scroll_region(&mut self, top: usize, bottom: usize) -> io::Result<()> {
+        write!(self, "{}", ScrollRegion(top, bottom))
+    }
+
+    /// Writes [`ResetScrollRegion`].
+    fn reset_scroll_region(&mut self) -> io::Result<()> {
+        write!(self, "{ResetScrollRegion}")
+    }
+}
+
+impl<W: io::Write + ?Sized> ScreenExt for W {}
+
+/// Writer which stays on the alternate screen until it's dropped, going
+/// back to the main screen even if the thread panics. Everything written
+/// to it goes to the writer it holds.
+/// 
+/// *Note: The main screen isn't gone back to if the program exits without
+/// unwinding, such as with [`std::process::exit`] or `panic = "abort"`.*
+/// 
+/// ---
+/// ## Examples
+/// ```no_run
+/// use std::io::{self, Write};
+/// use utils::ansi::{cursor::CursorExt, screen::AlternateScreen};
+/// 
+/// let mut screen = AlternateScreen::new(io::stdout().lock())?;
+/// screen.move_home()?;
+/// writeln!(screen, "Press enter to go back")?;
+/// screen.flush()?;
+/// io::stdin().read_line(&mut String::new())?;
+/// # Ok::<(), io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct AlternateScreen<W: io::Write> {
+    writer: W,
+}
+
+impl<W: io::Write> AlternateScreen<W> {
+    /// Switches to the alternate screen by writing [`EnterAlternateScreen`]
+    /// to `writer`.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.enter_alternate_screen()?;
+        writer.flush()?;
+        Ok(Self { writer })
+    }
+
+    /// The writer the alternate screen was switched to on.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// The writer the alternate screen was switched to on.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+}
+
+impl<W: io::Write> io::Write for AlternateScreen<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: io::Write> Drop for AlternateScreen<W> {
+    fn drop(&mut self) {
+        // Errors can't be returned here, there is nothing else to be done
+        // about them.
+        let _ = self.writer.leave_alternate_screen();
+        let _ = self.writer.flush();
+    }
+}
+
+// clears whole screen
+pub fn clear() {
+    print!("{}", Clear::All);
+}
+
+// clears whole line the cursor is on
+pub fn clear_line() {
+    print!("{}", ClearLine::All);
+}
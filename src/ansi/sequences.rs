@@ -0,0 +1,149 @@
+use std::borrow::Cow;
+
+use unicode_width::UnicodeWidthStr;
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+/// Length in bytes of the escape sequence at the start of `s`, which must
+/// start with an `ESC`. Unterminated sequences run to the end of `s`.
+pub(crate) fn sequence_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    debug_assert_eq!(bytes.first(), Some(&ESC));
+
+    match bytes.get(1) {
+        None => 1,
+        // CSI, parameters and intermediates followed by a final byte.
+        Some(b'[') => bytes[2..]
+            .iter()
+            .position(|b| (0x40..=0x7e).contains(b))
+            .map_or(bytes.len(), |i| i + 3),
+        // OSC, DCS, SOS, PM and APC, ended by ST and for OSC also BEL.
+        Some(b']' | b'P' | b'X' | b'^' | b'_') => {
+            let mut i = 2;
+            while i < bytes.len() {
+                match bytes[i] {
+                    BEL if bytes[1] == b']' => return i + 1,
+                    ESC if bytes.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            bytes.len()
+        },
+        // Intermediates followed by a final byte, like `ESC ( B`. Without
+        // a final byte the sequence ends after the intermediates, so that
+        // the text after it isn't split inside a character.
+        Some(0x20..=0x2f) => match bytes[2..].iter().position(|b| !(0x20..=0x2f).contains(b)) {
+            Some(i) if (0x30..=0x7e).contains(&bytes[i + 2]) => i + 3,
+            Some(i) => i + 2,
+            None => bytes.len(),
+        },
+        // Two byte sequences, like `ESC M` or `ESC 7`. Anything else
+        // leaves the ESC on its own.
+        Some(0x30..=0x7e) => 2,
+        Some(_) => 1,
+    }
+}
+
+/// Splits `s` into its text and escape sequences, as `(is_escape, part)`.
+pub(crate) fn split(s: &str) -> impl Iterator<Item = (bool, &str)> {
+    let mut rest = s;
+
+    std::iter::from_fn(move || {
+        let len = match rest.as_bytes().first()? {
+            &ESC => sequence_len(rest),
+            _ => rest.find('\x1b').unwrap_or(rest.len()),
+        };
+        let (part, next) = rest.split_at(len);
+        rest = next;
+
+        Some((part.starts_with('\x1b'), part))
+    })
+}
+
+/// Removes every ansi escape sequence from `s`, such as colours and styles,
+/// hyperlinks and other OSC sequences and cursor movement, leaving only the
+/// text. Only allocates if there is anything to remove.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::strip_ansi;
+/// 
+/// let output = strip_ansi("\x1b[1m\x1b[32mHello,\x1b[0m \x1b[Hworld!\x1bM");
+/// assert_eq!(output, "Hello, world!");
+/// 
+/// let link = "\x1b]8;;https://example.com\x1b\\example\x1b]8;;\x1b\\";
+/// assert_eq!(strip_ansi(link), "example");
+/// ```
+pub fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains('\x1b') {
+        return Cow::Borrowed(s);
+    }
+
+    Cow::Owned(
+        split(s)
+            .filter_map(|(is_escape, part)| (!is_escape).then_some(part))
+            .collect()
+    )
+}
+
+/// The number of columns `s` takes up in a terminal, ignoring any ansi
+/// escape sequences and counting wide characters, such as CJK and emoji,
+/// as two columns.
+/// 
+/// *Note: This is meant for a single line, control characters like `\n`
+/// are not treated specially.*
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::visible_width;
+/// 
+/// assert_eq!(visible_width("\x1b[31mHello\x1b[0m"), 5);
+/// assert_eq!(visible_width("\x1b[1m日本語\x1b[0m"), 6);
+/// assert_eq!(visible_width("👍 ok"), 5);
+/// ```
+pub fn visible_width(s: &str) -> usize {
+    split(s)
+        .filter(|(is_escape, _)| !is_escape)
+        .map(|(_, part)| part.width())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intermediates_without_final_byte() {
+        assert_eq!(strip_ansi("\x1b(é"), "é");
+        assert_eq!(strip_ansi("\x1b( \u{1F600}x"), "\u{1F600}x");
+        assert_eq!(visible_width("a\x1b#日本"), 5);
+        assert_eq!(sequence_len("\x1b(é"), 2);
+    }
+
+    #[test]
+    fn intermediates_with_final_byte() {
+        assert_eq!(sequence_len("\x1b(Bx"), 3);
+        assert_eq!(sequence_len("\x1b#8"), 3);
+        assert_eq!(strip_ansi("\x1b(0x\x1b(B"), "x");
+    }
+
+    #[test]
+    fn unterminated_sequences() {
+        assert_eq!(strip_ansi("a\x1b[1;3"), "a");
+        assert_eq!(strip_ansi("a\x1b]8;;é"), "a");
+        assert_eq!(strip_ansi("a\x1b( "), "a");
+        assert_eq!(strip_ansi("a\x1b"), "a");
+    }
+
+    #[test]
+    fn non_ascii_after_esc() {
+        for s in ["\x1bé", "\x1b[é", "\x1b]é", "\x1b é", "\x1b\x1bé", "é\x1b"] {
+            let parts: String = split(s).map(|(_, part)| part).collect();
+            assert_eq!(parts, s);
+        }
+        assert_eq!(strip_ansi("\x1bé"), "é");
+    }
+}
@@ -0,0 +1,254 @@
+use std::fmt;
+
+use super::palette::ColorDepth;
+use super::sequences::split;
+use super::Style;
+
+/// A run of text in a single [`Style`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub style: Style,
+    pub text: String,
+}
+
+impl Span {
+    pub fn new(style: Style, text: impl Into<String>) -> Self {
+        Self { style, text: text.into() }
+    }
+}
+
+/// An escape sequence other than SGR found while parsing, holding the whole
+/// sequence as it was written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sequence<'a> {
+    /// A control sequence, such as the cursor movement `\x1b[2A`.
+    Csi(&'a str),
+    /// An operating system command, such as a hyperlink or window title.
+    Osc(&'a str),
+    /// Any other escape sequence, such as `\x1bM`.
+    Other(&'a str),
+}
+
+impl<'a> Sequence<'a> {
+    fn new(s: &'a str) -> Self {
+        match s.as_bytes().get(1) {
+            Some(b'[') => Sequence::Csi(s),
+            Some(b']') => Sequence::Osc(s),
+            _ => Sequence::Other(s),
+        }
+    }
+
+    /// The escape sequence as it was written.
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            Sequence::Csi(s) | Sequence::Osc(s) | Sequence::Other(s) => s,
+        }
+    }
+}
+
+/// The parameters of `s` if it is an SGR sequence, `ESC [ params m`. The
+/// colon separated form of extended colours, `38:2::255:136:0`, is read as
+/// its `;` separated form.
+fn sgr_params(s: &str) -> Option<Vec<u16>> {
+    let params = s.strip_prefix("\x1b[")?.strip_suffix('m')?;
+    if params.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut out = Vec::new();
+    for param in params.split(';') {
+        let mut sub = param
+            .split(':')
+            .map(|p| if p.is_empty() { Some(0) } else { p.parse().ok() })
+            .collect::<Option<Vec<u16>>>()?;
+
+        match sub[..] {
+            // The colour space of `38:2:<space>:r:g:b` is skipped.
+            [38 | 48, 2, _, _, _, _] => {
+                sub.remove(2);
+            },
+            // Only the colours have sub parameters which matter, the kind
+            // of underline in `4:3` is read as a plain underline.
+            [38 | 48, ..] => (),
+            [4, 0, ..] => sub = vec![24],
+            _ => sub.truncate(1),
+        }
+        out.extend(sub);
+    }
+
+    Some(out)
+}
+
+/// Parses text containing ansi escape sequences into runs of styled text.
+/// SGR sequences, including 256 and RGB colours, set the style of the text
+/// after them and every other escape sequence is skipped, see
+/// [`parse_with`] to handle those.
+/// 
+/// Spans next to each other in the same style are joined, and styles which
+/// have no text in them are left out.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{Color, Style, spans::{self, Span}};
+/// 
+/// let output = spans::parse("\x1b[1;31mError:\x1b[0m file \x1b[38;5;208mnot\x1b[2A found");
+/// assert_eq!(output, [
+///     Span::new(Style::new().bold().fg(Color::Red), "Error:"),
+///     Span::new(Style::new(), " file "),
+///     Span::new(Style::new().fg(Color::Ansi256(208)), "not found"),
+/// ]);
+/// ```
+pub fn parse(s: &str) -> Vec<Span> {
+    parse_with(s, |_, _| ())
+}
+
+/// Version of [`parse`] which calls `f` with each escape sequence other
+/// than SGR, and the number of bytes of text before it.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::spans::{self, Sequence};
+/// 
+/// let mut sequences = Vec::new();
+/// let output = spans::parse_with("\x1b]0;title\x07Hello\x1b[2K", |at, seq| sequences.push((at, seq)));
+/// 
+/// assert_eq!(output[0].text, "Hello");
+/// assert_eq!(sequences, [(0, Sequence::Osc("\x1b]0;title\x07")), (5, Sequence::Csi("\x1b[2K"))]);
+/// ```
+pub fn parse_with<'a>(s: &'a str, mut f: impl FnMut(usize, Sequence<'a>)) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut style = Style::new();
+    let mut at = 0;
+
+    for (is_escape, part) in split(s) {
+        if !is_escape {
+            match spans.last_mut() {
+                Some(span) if span.style == style => span.text.push_str(part),
+                _ => spans.push(Span::new(style, part)),
+            }
+            at += part.len();
+        } else if let Some(params) = sgr_params(part) {
+            style.apply_sgr(&params);
+        } else {
+            f(at, Sequence::new(part));
+        }
+    }
+
+    spans
+}
+
+/// Writes spans back out as text with the fewest escape codes needed,
+/// changing only what differs between one span and the next and resetting
+/// at the end. The colours are written as they are, regardless of the
+/// terminal.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::spans;
+/// 
+/// let output = spans::render(&spans::parse("\x1b[1m\x1b[31mError:\x1b[0m\x1b[1m bold\x1b[0m"));
+/// assert_eq!(output, "\x1b[1;31mError:\x1b[39m bold\x1b[0m");
+/// ```
+pub fn render(spans: &[Span]) -> String {
+    let mut out = String::new();
+
+    render_into(spans, &mut out).expect("writing to a String cannot fail");
+
+    out
+}
+
+/// Non-allocating version of [`render`] which writes the spans into any
+/// [`fmt::Write`].
+pub fn render_into(spans: &[Span], w: &mut impl fmt::Write) -> fmt::Result {
+    let mut current = Style::new();
+
+    for span in spans.iter().filter(|span| !span.text.is_empty()) {
+        current.write_change(&span.style, ColorDepth::TrueColor, w)?;
+        w.write_str(&span.text)?;
+        current = span.style;
+    }
+
+    current.write_change(&Style::new(), ColorDepth::TrueColor, w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::Color;
+
+    #[test]
+    fn colon_separated_colours() {
+        let orange = Style::new().fg(Color::Rgb(255, 136, 0));
+        assert_eq!(parse("\x1b[38:2::255:136:0mx"), [Span::new(orange, "x")]);
+        assert_eq!(parse("\x1b[38:2:255:136:0mx"), [Span::new(orange, "x")]);
+        assert_eq!(parse("\x1b[48:5:208mx"), [Span::new(Style::new().bg(Color::Ansi256(208)), "x")]);
+    }
+
+    #[test]
+    fn underline_kinds() {
+        assert_eq!(parse("\x1b[4:3mx"), [Span::new(Style::new().underline(), "x")]);
+        assert_eq!(parse("\x1b[4m\x1b[1mx\x1b[4:0my"), [
+            Span::new(Style::new().underline().bold(), "x"),
+            Span::new(Style::new().bold(), "y"),
+        ]);
+    }
+
+    #[test]
+    fn incomplete_extended_colours() {
+        let red = Style::new().fg(Color::Red);
+        assert_eq!(parse("\x1b[31;38;5mx"), [Span::new(red, "x")]);
+        assert_eq!(parse("\x1b[31;38;2;1;2mx"), [Span::new(red, "x")]);
+        assert_eq!(parse("\x1b[38;5;300mx"), [Span::new(Style::new().fg(Color::Ansi256(255)), "x")]);
+    }
+
+    #[test]
+    fn resets_and_empty_styles() {
+        assert_eq!(parse(""), []);
+        assert_eq!(parse("\x1b[1m\x1b[0m"), []);
+        assert_eq!(parse("\x1b[1mx\x1b[my"), [Span::new(Style::new().bold(), "x"), Span::new(Style::new(), "y")]);
+        assert_eq!(parse("a\x1b[2Kb\x1b[1m\x1b[22mc"), [Span::new(Style::new(), "abc")]);
+    }
+
+    #[test]
+    fn other_sequences_are_reported() {
+        let mut sequences = Vec::new();
+        let output = parse_with("a\x1b[?25lb\x1bMc\x1b]8;;x\x1b\\d\x1b[1;3", |at, seq| sequences.push((at, seq)));
+
+        assert_eq!(output, [Span::new(Style::new(), "abcd")]);
+        assert_eq!(sequences, [
+            (1, Sequence::Csi("\x1b[?25l")),
+            (2, Sequence::Other("\x1bM")),
+            (3, Sequence::Osc("\x1b]8;;x\x1b\\")),
+            (4, Sequence::Csi("\x1b[1;3")),
+        ]);
+        assert_eq!(sequences[2].1.as_str(), "\x1b]8;;x\x1b\\");
+    }
+
+    #[test]
+    fn render_round_trips() {
+        for s in [
+            "plain",
+            "\x1b[1;31mError:\x1b[0m file \x1b[38;5;208mnot\x1b[0m found",
+            "\x1b[1m\x1b[2mbold dim\x1b[22m\x1b[2m dim\x1b[0m",
+            "\x1b[4;9;48;2;32;32;32mx\x1b[24my\x1b[49mz",
+            "\x1b[7mreversed\x1b[27m\x1b[91mbright",
+        ] {
+            let spans = parse(s);
+            assert_eq!(parse(&render(&spans)), spans, "{s:?}");
+        }
+    }
+
+    #[test]
+    fn render_writes_the_least() {
+        assert_eq!(render(&[]), "");
+        assert_eq!(render(&[Span::new(Style::new(), "x")]), "x");
+        assert_eq!(render(&[Span::new(Style::new().bold(), ""), Span::new(Style::new(), "x")]), "x");
+
+        let bold = Style::new().bold().fg(Color::Red);
+        assert_eq!(render(&[Span::new(bold, "a"), Span::new(bold.italic(), "b")]), "\x1b[1;31ma\x1b[3mb\x1b[0m");
+        assert_eq!(render(&[Span::new(bold, "a"), Span::new(Style::new().dim(), "b")]), "\x1b[1;31ma\x1b[0;2mb\x1b[0m");
+    }
+}
@@ -0,0 +1,712 @@
+use std::fmt;
+use std::str::FromStr;
+
+use util_markup::codes::*;
+
+use super::palette::{self, ColorDepth};
+use super::policy::{self, Stream};
+use super::tokens::{Token, Tokens};
+
+/// A colour for the foreground or background of text.
+/// 
+/// The first 8 are the standard colours, followed by their bright
+/// variants, matching the constants [`super::RED`], [`super::BRRED`], etc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// A colour of the 256 colour palette, see [`super::palette`].
+    Ansi256(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    const ANSI16: [Color; 16] = [
+        Color::Black, Color::Red, Color::Green, Color::Yellow,
+        Color::Blue, Color::Magenta, Color::Cyan, Color::White,
+        Color::BrightBlack, Color::BrightRed, Color::BrightGreen, Color::BrightYellow,
+        Color::BrightBlue, Color::BrightMagenta, Color::BrightCyan, Color::BrightWhite,
+    ];
+
+    /// The standard or bright colour with `index` from 0 to 15, as used by
+    /// the 256 colour palette.
+    pub fn from_ansi16(index: u8) -> Option<Self> {
+        Self::ANSI16.get(index as usize).copied()
+    }
+
+    /// The index from 0 to 15 of a standard or bright colour.
+    pub fn ansi16_index(self) -> Option<u8> {
+        Self::ANSI16.iter().position(|c| *c == self).map(|i| i as u8)
+    }
+
+    /// Turns the colour into the closest looking colour a terminal which
+    /// can show `depth` colours can show.
+    /// 
+    /// ---
+    /// ## Examples
+    /// ```
+    /// use utils::ansi::{Color, palette::ColorDepth};
+    /// 
+    /// assert_eq!(Color::Rgb(255, 135, 0).to_depth(ColorDepth::Ansi256), Color::Ansi256(208));
+    /// assert_eq!(Color::Ansi256(196).to_depth(ColorDepth::Ansi16), Color::BrightRed);
+    /// assert_eq!(Color::Red.to_depth(ColorDepth::Ansi16), Color::Red);
+    /// ```
+    pub fn to_depth(self, depth: ColorDepth) -> Self {
+        match (self, depth) {
+            (Color::Rgb(r, g, b), ColorDepth::Ansi256) => {
+                Color::Ansi256(palette::rgb_to_ansi256([r, g, b]))
+            },
+            (Color::Rgb(r, g, b), ColorDepth::Ansi16) => {
+                Self::ANSI16[palette::rgb_to_ansi16([r, g, b]) as usize]
+            },
+            (Color::Ansi256(index), ColorDepth::Ansi16) => {
+                Self::ANSI16[palette::ansi256_to_ansi16(index) as usize]
+            },
+            (color, _) => color,
+        }
+    }
+
+    /// The RGB value of the colour, with the standard and bright colours
+    /// taken from the xterm palette in [`palette::ANSI16`].
+    /// 
+    /// ---
+    /// ## Examples
+    /// ```
+    /// use utils::ansi::Color;
+    /// 
+    /// assert_eq!(Color::Red.to_rgb(), [205, 0, 0]);
+    /// assert_eq!(Color::Ansi256(208).to_rgb(), [255, 135, 0]);
+    /// ```
+    pub fn to_rgb(self) -> [u8; 3] {
+        match self {
+            Color::Ansi256(index) => palette::ansi256_to_rgb(index),
+            Color::Rgb(r, g, b) => [r, g, b],
+            color => palette::ANSI16[color.ansi16_index().unwrap_or_default() as usize],
+        }
+    }
+
+    /// The constants of the standard and bright colours, as the foreground
+    /// and as the background.
+    const CODES: [(&'static str, &'static str); 16] = [
+        (BLK, BBLK), (RED, BRED), (GRN, BGRN), (YLW, BYLW),
+        (BLU, BBLU), (MGT, BMGT), (CYN, BCYN), (WHT, BWHT),
+        (BRBLK, BBRBLK), (BRRED, BBRRED), (BRGRN, BBRGRN), (BRYLW, BBRYLW),
+        (BRBLU, BBRBLU), (BRMGT, BBRMGT), (BRCYN, BBRCYN), (BRWHT, BBRWHT),
+    ];
+
+    /// Writes the escape code setting the colour as the foreground or
+    /// `background`, the constant for a standard or bright colour.
+    fn write_code(self, background: bool, w: &mut impl fmt::Write) -> fmt::Result {
+        match self.ansi16_index().map(|index| Self::CODES[index as usize]) {
+            Some((fg, bg)) => w.write_str(if background { bg } else { fg }),
+            None => {
+                w.write_str("\x1b[")?;
+                self.write_params(background, w)?;
+                w.write_str("m")
+            },
+        }
+    }
+
+    /// Writes the SGR parameters setting the colour as the foreground or
+    /// `background`, e.g. `31` or `48;5;208`.
+    fn write_params(self, background: bool, w: &mut impl fmt::Write) -> fmt::Result {
+        let layer = if background { 48 } else { 38 };
+
+        match self {
+            Color::Ansi256(index) => write!(w, "{layer};5;{index}"),
+            Color::Rgb(r, g, b) => write!(w, "{layer};2;{r};{g};{b}"),
+            color => {
+                let index = color.ansi16_index().unwrap_or_default();
+                write!(w, "{}", palette::ansi16_sgr(index, background))
+            },
+        }
+    }
+}
+
+/// A text style which can be turned on and off on its own, as used by
+/// [`Style::attr`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attribute {
+    Bold,
+    Dim,
+    Italic,
+    Underline,
+    Blink,
+    Reverse,
+    Hidden,
+    Strikethrough,
+    DoubleUnderline,
+    Overline,
+}
+
+impl Attribute {
+    const ALL: [Attribute; 10] = [
+        Attribute::Bold, Attribute::Dim, Attribute::Italic, Attribute::Underline,
+        Attribute::Blink, Attribute::Reverse, Attribute::Hidden, Attribute::Strikethrough,
+        Attribute::DoubleUnderline, Attribute::Overline,
+    ];
+
+    fn bit(self) -> u16 {
+        1 << self as u16
+    }
+
+    /// The constant turning the attribute on.
+    fn code(self) -> &'static str {
+        match self {
+            Attribute::Bold => BLD,
+            Attribute::Dim => DIM,
+            Attribute::Italic => ITL,
+            Attribute::Underline => UND,
+            Attribute::Blink => BLN,
+            Attribute::Reverse => REV,
+            Attribute::Hidden => HID,
+            Attribute::Strikethrough => STK,
+            Attribute::DoubleUnderline => DUN,
+            Attribute::Overline => OVL,
+        }
+    }
+
+    /// The SGR parameters turning the attribute on and off.
+    fn sgr(self) -> (u16, u16) {
+        match self {
+            Attribute::Bold => (1, 22),
+            Attribute::Dim => (2, 22),
+            Attribute::Italic => (3, 23),
+            Attribute::Underline => (4, 24),
+            Attribute::Blink => (5, 25),
+            Attribute::Reverse => (7, 27),
+            Attribute::Hidden => (8, 28),
+            Attribute::Strikethrough => (9, 29),
+            Attribute::DoubleUnderline => (21, 24),
+            Attribute::Overline => (53, 55),
+        }
+    }
+}
+
+/// A combination of a foreground colour, a background colour and any
+/// [`Attribute`]s, built up one at a time.
+/// 
+/// Displaying a style writes the escape codes turning it on, following
+/// [`super::policy`] like [`super::f_color::rgb`] does. These are the
+/// constants of the module, such as [`BLD`] and [`RED`], with 256 and RGB
+/// colours written like [`super::f_color::ansi256`] and
+/// [`super::f_color::rgb`].
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{Color, Style, policy::{self, ColorMode}, BBLU, BLD, RED, UND};
+/// 
+/// policy::set_mode(ColorMode::Always);
+/// 
+/// let style = Style::new().fg(Color::Red).bg(Color::Blue).bold().underline();
+/// assert_eq!(style.to_string(), format!("{BLD}{UND}{RED}{BBLU}"));
+/// assert_eq!(style.paint("Error").to_string(), "\x1b[1m\x1b[4m\x1b[31m\x1b[44mError\x1b[0m");
+/// 
+/// // Styles can be read back from the constants and markup codes.
+/// assert_eq!(Style::from_escape(&format!("{BLD}{RED}")), Some(Style::new().bold().fg(Color::Red)));
+/// assert_eq!("_BLD.RED".parse(), Ok(Style::new().bold().fg(Color::Red)));
+/// assert_eq!(Style::new().bold().fg(Color::Red).markup(), "_BLD.RED");
+/// assert_eq!(Style::new().bg(Color::Rgb(32, 32, 32)).markup(), "#(#202020)");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Style {
+    fg: Option<Color>,
+    bg: Option<Color>,
+    attrs: u16,
+}
+
+impl Style {
+    /// A style which changes nothing.
+    pub const fn new() -> Self {
+        Self { fg: None, bg: None, attrs: 0 }
+    }
+
+    pub fn fg(mut self, color: Color) -> Self {
+        self.fg = Some(color);
+        self
+    }
+
+    pub fn bg(mut self, color: Color) -> Self {
+        self.bg = Some(color);
+        self
+    }
+
+    pub fn attr(mut self, attr: Attribute) -> Self {
+        self.attrs |= attr.bit();
+        self
+    }
+
+    pub fn bold(self) -> Self {
+        self.attr(Attribute::Bold)
+    }
+
+    pub fn dim(self) -> Self {
+        self.attr(Attribute::Dim)
+    }
+
+    pub fn italic(self) -> Self {
+        self.attr(Attribute::Italic)
+    }
+
+    pub fn underline(self) -> Self {
+        self.attr(Attribute::Underline)
+    }
+
+    pub fn blink(self) -> Self {
+        self.attr(Attribute::Blink)
+    }
+
+    pub fn reverse(self) -> Self {
+        self.attr(Attribute::Reverse)
+    }
+
+    pub fn hidden(self) -> Self {
+        self.attr(Attribute::Hidden)
+    }
+
+    pub fn strikethrough(self) -> Self {
+        self.attr(Attribute::Strikethrough)
+    }
+
+    pub fn double_underline(self) -> Self {
+        self.attr(Attribute::DoubleUnderline)
+    }
+
+    pub fn overline(self) -> Self {
+        self.attr(Attribute::Overline)
+    }
+
+    pub fn foreground(&self) -> Option<Color> {
+        self.fg
+    }
+
+    pub fn background(&self) -> Option<Color> {
+        self.bg
+    }
+
+    pub fn has(&self, attr: Attribute) -> bool {
+        self.attrs & attr.bit() != 0
+    }
+
+    /// The [`Attribute`]s which are set.
+    pub fn attributes(&self) -> impl Iterator<Item = Attribute> + '_ {
+        Attribute::ALL.into_iter().filter(|attr| self.has(*attr))
+    }
+
+    /// Whether the style changes nothing.
+    pub fn is_plain(&self) -> bool {
+        *self == Self::new()
+    }
+
+    /// Wraps `value` so that displaying it writes it in this style,
+    /// followed by a reset.
+    pub fn paint<T>(self, value: T) -> Styled<T> {
+        Styled { style: self, value }
+    }
+
+    /// Writes the escape codes turning the style on, with its colours
+    /// turned into ones a terminal which can show `depth` colours can show.
+    /// Writes nothing for a plain style.
+    pub(crate) fn write_escape(&self, depth: ColorDepth, w: &mut impl fmt::Write) -> fmt::Result {
+        for attr in self.attributes() {
+            w.write_str(attr.code())?;
+        }
+        for (color, background) in [(self.fg, false), (self.bg, true)] {
+            if let Some(color) = color {
+                color.to_depth(depth).write_code(background, w)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the SGR parameters turning the style on, separated by `;`.
+    fn write_sgr(&self, depth: ColorDepth, w: &mut impl fmt::Write) -> fmt::Result {
+        let mut sep = "";
+        for attr in self.attributes() {
+            write!(w, "{sep}{}", attr.sgr().0)?;
+            sep = ";";
+        }
+        for (color, background) in [(self.fg, false), (self.bg, true)] {
+            if let Some(color) = color {
+                w.write_str(sep)?;
+                color.to_depth(depth).write_params(background, w)?;
+                sep = ";";
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the shortest SGR sequence which changes the style of the
+    /// terminal from this style to `to`. Writes nothing if they are equal.
+    pub(crate) fn write_change(&self, to: &Style, depth: ColorDepth, w: &mut impl fmt::Write) -> fmt::Result {
+        if self == to {
+            return Ok(());
+        }
+        if to.is_plain() {
+            return w.write_str("\x1b[0m");
+        }
+
+        let mut full = String::from("0;");
+        to.write_sgr(depth, &mut full)?;
+
+        // Bold and dim are turned off together, as are the underlines, so
+        // turning one off can mean turning the other back on.
+        let mut offs = Vec::new();
+        for attr in Attribute::ALL.into_iter().filter(|&attr| self.has(attr) && !to.has(attr)) {
+            if !offs.contains(&attr.sgr().1) {
+                offs.push(attr.sgr().1);
+            }
+        }
+        let mut params: Vec<String> = offs.iter().map(u16::to_string).collect();
+        for attr in Attribute::ALL.into_iter().filter(|&attr| to.has(attr)) {
+            if !self.has(attr) || offs.contains(&attr.sgr().1) {
+                params.push(attr.sgr().0.to_string());
+            }
+        }
+        for (from, to, background) in [(self.fg, to.fg, false), (self.bg, to.bg, true)] {
+            match to {
+                _ if from == to => (),
+                None => params.push(if background { "49" } else { "39" }.to_string()),
+                Some(color) => {
+                    let mut param = String::new();
+                    color.to_depth(depth).write_params(background, &mut param)?;
+                    params.push(param);
+                },
+            }
+        }
+        let diff = params.join(";");
+
+        let params = if full.len() < diff.len() { full } else { diff };
+        write!(w, "\x1b[{params}m")
+    }
+
+    /// Applies the parameters of one SGR sequence, `ESC [ params m`, to
+    /// the style. Unknown parameters are skipped.
+    pub(crate) fn apply_sgr(&mut self, params: &[u16]) {
+        // Parses the rest of a `38`/`48` extended colour.
+        fn extended(params: &mut impl Iterator<Item = u16>) -> Option<Color> {
+            match params.next()? {
+                5 => params.next().map(|i| Color::Ansi256(i.min(255) as u8)),
+                2 => {
+                    let mut c = || params.next().map(|c| c.min(255) as u8);
+                    Some(Color::Rgb(c()?, c()?, c()?))
+                },
+                _ => None,
+            }
+        }
+
+        // `ESC [ m` is the same as `ESC [ 0 m`.
+        if params.is_empty() {
+            *self = Self::new();
+        }
+
+        let mut params = params.iter().copied();
+        while let Some(param) = params.next() {
+            match param {
+                0 => *self = Self::new(),
+                30..=37 => self.fg = Color::from_ansi16(param as u8 - 30),
+                90..=97 => self.fg = Color::from_ansi16(param as u8 - 90 + 8),
+                40..=47 => self.bg = Color::from_ansi16(param as u8 - 40),
+                100..=107 => self.bg = Color::from_ansi16(param as u8 - 100 + 8),
+                38 => self.fg = extended(&mut params).or(self.fg),
+                48 => self.bg = extended(&mut params).or(self.bg),
+                39 => self.fg = None,
+                49 => self.bg = None,
+                _ => {
+                    for attr in Attribute::ALL {
+                        let (on, off) = attr.sgr();
+                        if param == on {
+                            self.attrs |= attr.bit();
+                        } else if param == off {
+                            self.attrs &= !attr.bit();
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Applies one or more SGR escape codes to the style, returning
+    /// [`None`] if `s` is anything else.
+    fn apply_escape(&mut self, s: &str) -> Option<()> {
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            let seq = rest.strip_prefix("\x1b[")?;
+            let end = seq.find('m')?;
+            let params = seq[..end]
+                .split(';')
+                .filter(|p| !p.is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<u16>, _>>()
+                .ok()?;
+
+            self.apply_sgr(&params);
+            rest = &seq[end + 1..];
+        }
+
+        Some(())
+    }
+
+    /// Reads a style back from one or more SGR escape codes, such as the
+    /// constants in [`super`] or the output of displaying a [`Style`].
+    /// Returns [`None`] if `s` is anything else.
+    pub fn from_escape(s: &str) -> Option<Self> {
+        let mut style = Self::new();
+        style.apply_escape(s)?;
+
+        Some(style)
+    }
+
+    /// The style written as markup codes, as used by [`super::replace_cc`].
+    /// RGB colours are written in their bracketed hex form, `.(#ff8800)`.
+    pub fn markup(&self) -> String {
+        let mut out = String::new();
+
+        for attr in self.attributes() {
+            out.push_str(match attr {
+                Attribute::Bold => "_BLD",
+                Attribute::Dim => "_DIM",
+                Attribute::Italic => "_ITL",
+                Attribute::Underline => "_UND",
+                Attribute::Blink => "_BLN",
+                Attribute::Reverse => "_REV",
+                Attribute::Hidden => "_HID",
+                Attribute::Strikethrough => "_STK",
+                Attribute::DoubleUnderline => "_DUN",
+                Attribute::Overline => "_OVL",
+            });
+        }
+
+        const NAMES: [&str; 8] = ["BLK", "RED", "GRN", "YLW", "BLU", "MGT", "CYN", "WHT"];
+        for (color, sigil) in [(self.fg, '.'), (self.bg, '#')] {
+            match color {
+                None => (),
+                Some(Color::Rgb(r, g, b)) => out.push_str(&format!("{sigil}(#{r:02x}{g:02x}{b:02x})")),
+                Some(Color::Ansi256(index)) => out.push_str(&format!("{sigil}C{index}")),
+                Some(color) => {
+                    let index = color.ansi16_index().unwrap_or_default() as usize;
+                    let bright = if index >= 8 { "B" } else { "" };
+                    out.push_str(&format!("{sigil}{bright}{}", NAMES[index % 8]));
+                },
+            }
+        }
+
+        out
+    }
+}
+
+impl fmt::Display for Style {
+    /// Writes the escape code turning the style on, or nothing if colour
+    /// is disabled.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !policy::enabled(Stream::Stdout) {
+            return Ok(());
+        }
+
+        self.write_escape(policy::depth(), f)
+    }
+}
+
+/// The error returned when parsing a [`Style`] from markup fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseStyleError {
+    text: String,
+}
+
+impl fmt::Display for ParseStyleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a markup code", self.text)
+    }
+}
+
+impl std::error::Error for ParseStyleError {}
+
+impl FromStr for Style {
+    type Err = ParseStyleError;
+
+    /// Parses a run of markup codes, such as `_BLD.RED#C236.orange`, into the
+    /// style they add up to.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Self::new();
+
+        for token in Tokens::new(s) {
+            match token {
+                Token::Text(text) => return Err(ParseStyleError { text: text.to_string() }),
+                Token::Code(code) => {
+                    style.apply_escape(code);
+                },
+                Token::Ansi256 { background: false, index } => style.fg = Some(Color::Ansi256(index)),
+                Token::Ansi256 { background: true, index } => style.bg = Some(Color::Ansi256(index)),
+                Token::Rgb { background: false, rgb: [r, g, b] } => style.fg = Some(Color::Rgb(r, g, b)),
+                Token::Rgb { background: true, rgb: [r, g, b] } => style.bg = Some(Color::Rgb(r, g, b)),
+                // Links only come from tags, which aren't read here.
+                Token::Link(_) | Token::LinkEnd(_) => (),
+            }
+        }
+
+        Ok(style)
+    }
+}
+
+/// A value which is displayed in a [`Style`], created by [`Style::paint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Styled<T> {
+    style: Style,
+    value: T,
+}
+
+impl<T> Styled<T> {
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Styled<T> {
+    /// Writes the value in its style followed by a reset, or just the
+    /// value if colour is disabled. Any width, precision or alignment is
+    /// applied to the value, so padding is styled along with it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.style.is_plain() || !policy::enabled(Stream::Stdout) {
+            return self.value.fmt(f);
+        }
+
+        self.style.write_escape(policy::depth(), f)?;
+        self.value.fmt(f)?;
+        f.write_str(X)
+    }
+}
+
+/// Calls `$m!` with every shorthand of [`Colorize`], as the name of the
+/// shorthand and the [`Style`] builder method it calls.
+macro_rules! shorthands {
+    ($m:ident) => {
+        $m! {
+            black => fg(Color::Black), red => fg(Color::Red),
+            green => fg(Color::Green), yellow => fg(Color::Yellow),
+            blue => fg(Color::Blue), magenta => fg(Color::Magenta),
+            cyan => fg(Color::Cyan), white => fg(Color::White),
+            bright_black => fg(Color::BrightBlack), bright_red => fg(Color::BrightRed),
+            bright_green => fg(Color::BrightGreen), bright_yellow => fg(Color::BrightYellow),
+            bright_blue => fg(Color::BrightBlue), bright_magenta => fg(Color::BrightMagenta),
+            bright_cyan => fg(Color::BrightCyan), bright_white => fg(Color::BrightWhite),
+            on_black => bg(Color::Black), on_red => bg(Color::Red),
+            on_green => bg(Color::Green), on_yellow => bg(Color::Yellow),
+            on_blue => bg(Color::Blue), on_magenta => bg(Color::Magenta),
+            on_cyan => bg(Color::Cyan), on_white => bg(Color::White),
+            on_bright_black => bg(Color::BrightBlack), on_bright_red => bg(Color::BrightRed),
+            on_bright_green => bg(Color::BrightGreen), on_bright_yellow => bg(Color::BrightYellow),
+            on_bright_blue => bg(Color::BrightBlue), on_bright_magenta => bg(Color::BrightMagenta),
+            on_bright_cyan => bg(Color::BrightCyan), on_bright_white => bg(Color::BrightWhite),
+            bold => bold(), dim => dim(), italic => italic(), underline => underline(),
+            blink => blink(), reverse => reverse(), hidden => hidden(),
+            strikethrough => strikethrough(), double_underline => double_underline(),
+            overline => overline(),
+        }
+    };
+}
+
+macro_rules! trait_shorthands {
+    ($($name:ident => $method:ident($($arg:expr)?)),* $(,)?) => {
+        $(
+            #[doc = concat!("Wraps the value in a [`Styled`] with [`Style::", stringify!($method), "`].")]
+            fn $name(self) -> Styled<Self> {
+                Style::new().$method($($arg)?).paint(self)
+            }
+        )*
+    };
+}
+
+macro_rules! styled_shorthands {
+    ($($name:ident => $method:ident($($arg:expr)?)),* $(,)?) => {
+        $(
+            #[doc = concat!("Adds [`Style::", stringify!($method), "`] to the style.")]
+            pub fn $name(mut self) -> Self {
+                self.style = self.style.$method($($arg)?);
+                self
+            }
+        )*
+    };
+}
+
+/// Extension trait for styling anything which implements [`fmt::Display`],
+/// such as `&str` and [`String`], with chained calls.
+/// 
+/// Each call returns a [`Styled`] which is only formatted when it is
+/// displayed, and further calls on it add to its style rather than
+/// wrapping it again. It follows [`super::policy`], and applies any width,
+/// precision and alignment to the value inside the style.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::{Colorize, Color, policy::{self, ColorDepth, ColorMode}, BBLU, BLD, RED, X};
+/// 
+/// policy::set_mode(ColorMode::Always);
+/// policy::set_depth(Some(ColorDepth::TrueColor));
+/// 
+/// assert_eq!("Error".red().bold().on_blue().to_string(), format!("{BLD}{RED}{BBLU}Error{X}"));
+/// assert_eq!(String::from("ok").fg(Color::Ansi256(208)).to_string(), "\x1b[38;5;208mok\x1b[0m");
+/// assert_eq!(format!("[{:>4}]", 42.green()), "[\x1b[32m  42\x1b[0m]");
+/// assert_eq!(format!("{:.3}", "truncated".italic()), "\x1b[3mtru\x1b[0m");
+/// 
+/// policy::set_mode(ColorMode::Never);
+/// assert_eq!(format!("{:<6}|", "plain".red()), "plain |");
+/// ```
+pub trait Colorize: fmt::Display + Sized {
+    /// Wraps the value in a [`Styled`] with `style`.
+    fn paint(self, style: Style) -> Styled<Self> {
+        style.paint(self)
+    }
+
+    /// Wraps the value in a [`Styled`] with `color` as its foreground.
+    fn fg(self, color: Color) -> Styled<Self> {
+        Style::new().fg(color).paint(self)
+    }
+
+    /// Wraps the value in a [`Styled`] with `color` as its background.
+    fn bg(self, color: Color) -> Styled<Self> {
+        Style::new().bg(color).paint(self)
+    }
+
+    shorthands!(trait_shorthands);
+}
+
+impl<T: fmt::Display> Colorize for T {}
+
+impl<T> Styled<T> {
+    /// Sets the foreground of the style to `color`.
+    pub fn fg(mut self, color: Color) -> Self {
+        self.style = self.style.fg(color);
+        self
+    }
+
+    /// Sets the background of the style to `color`.
+    pub fn bg(mut self, color: Color) -> Self {
+        self.style = self.style.bg(color);
+        self
+    }
+
+    shorthands!(styled_shorthands);
+}
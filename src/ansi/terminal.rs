@@ -0,0 +1,619 @@
+#[cfg(unix)]
+pub use self::mode::*;
+#[cfg(unix)]
+pub use self::resize::*;
+
+/// The size of a terminal, in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Size {
+    pub columns: usize,
+    pub lines: usize,
+}
+
+/// The size of the terminal, or [`None`] if it can't be found.
+/// 
+/// It is the size of the terminal of the stdout, the stderr or the stdin,
+/// whichever is one first. Otherwise it is read from the `COLUMNS` and
+/// `LINES` environment variables, which is also the only way on platforms
+/// other than unix.
+/// 
+/// ---
+/// ## Examples
+/// Fitting a progress bar to the terminal:
+/// ```
+/// use utils::ansi::terminal;
+/// 
+/// let width = terminal::size().map_or(80, |size| size.columns);
+/// println!("[{}]", "#".repeat(width.saturating_sub(2)));
+/// ```
+pub fn size() -> Option<Size> {
+    #[cfg(unix)]
+    {
+        let found = [libc::STDOUT_FILENO, libc::STDERR_FILENO, libc::STDIN_FILENO]
+            .iter()
+            .find_map(|fd| size_of(fd).ok());
+        if found.is_some() {
+            return found;
+        }
+    }
+
+    let var = |name| std::env::var(name).ok()?.trim().parse().ok().filter(|&n| n > 0);
+    Some(Size { columns: var("COLUMNS")?, lines: var("LINES")? })
+}
+
+#[cfg(unix)]
+mod mode {
+    use std::io;
+    use std::mem::MaybeUninit;
+    use std::os::fd::{AsRawFd, RawFd};
+    use std::ptr;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Mutex, Once, OnceLock, PoisonError};
+
+    use signal_hook::low_level;
+    use signal_hook::SigId;
+
+    /// How the terminal hands input to the program.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum Mode {
+        /// Every byte is read as soon as it's typed, without being echoed,
+        /// and nothing is handled by the terminal. Ctrl-C is read as `\x03`
+        /// instead of sending `SIGINT`, and output isn't processed either,
+        /// so a new line needs `\r\n` to go back to the first column.
+        Raw,
+        /// Every key is read as soon as it's typed, without being echoed,
+        /// but Ctrl-C still sends `SIGINT` and output is processed as usual.
+        Cbreak,
+    }
+
+    /// The signals which put the terminals back before going on as they
+    /// would have without the guards.
+    const SIGNALS: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+    /// The [`ModeGuard`]s alive and the actions registered for each of
+    /// [`SIGNALS`].
+    struct State {
+        /// The attributes of the terminal of every guard from before it, in
+        /// the order they were created, for restoring them on a panic.
+        saved: Vec<(u64, RawFd, libc::termios)>,
+        actions: [Option<SigId>; 2],
+    }
+
+    static STATE: Mutex<State> = Mutex::new(State { saved: Vec::new(), actions: [None; 2] });
+
+    /// Whether each of [`SIGNALS`] ended the program before any action was
+    /// registered for it.
+    fn defaults() -> [bool; 2] {
+        static DEFAULTS: OnceLock<[bool; 2]> = OnceLock::new();
+
+        *DEFAULTS.get_or_init(|| {
+            SIGNALS.map(|signal| {
+                let mut action = MaybeUninit::<libc::sigaction>::uninit();
+                // SAFETY: `action` is valid for writes of a `sigaction`, and
+                // is only read once `sigaction` has filled it in.
+                unsafe {
+                    libc::sigaction(signal, ptr::null(), action.as_mut_ptr()) == 0
+                        && action.assume_init().sa_sigaction == libc::SIG_DFL
+                }
+            })
+        })
+    }
+
+    /// Registers the actions of [`SIGNALS`] for the guards of `state`,
+    /// replacing the ones from before. Each puts the terminals back, and
+    /// then ends the program if the signal would have, after which
+    /// signal-hook calls the handler the program had before.
+    /// 
+    /// The new actions are registered before the old ones are removed, so
+    /// a signal in between runs both. That's fine, as the terminal of a new
+    /// guard is only changed after this, and the terminal of a dropped one
+    /// already put back.
+    /// 
+    /// Once there are no guards the actions are removed, except for signals
+    /// which ended the program. Their signal-hook handler stays installed
+    /// and would ignore them without an action, so one ending the program
+    /// is kept.
+    fn register(state: &mut State) {
+        let saved: Vec<_> = state.saved.iter().map(|&(_, fd, termios)| (fd, termios)).collect();
+
+        for ((&signal, default), id) in SIGNALS.iter().zip(defaults()).zip(&mut state.actions) {
+            if saved.is_empty() && !default {
+                if let Some(old) = id.take() {
+                    low_level::unregister(old);
+                }
+                continue;
+            }
+
+            let saved = saved.clone();
+            let action = move || {
+                for (fd, termios) in saved.iter().rev() {
+                    let _ = set_attributes(*fd, termios);
+                }
+                if default {
+                    let _ = low_level::emulate_default_handler(signal);
+                }
+            };
+
+            // SAFETY: The action only calls `tcsetattr` and
+            // `emulate_default_handler`, which are async-signal-safe.
+            if let Ok(new) = unsafe { low_level::register(signal, action) } {
+                if let Some(old) = id.replace(new) {
+                    low_level::unregister(old);
+                }
+            }
+        }
+    }
+
+    fn check(result: libc::c_int) -> io::Result<()> {
+        if result == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn get_attributes(fd: RawFd) -> io::Result<libc::termios> {
+        let mut termios = MaybeUninit::uninit();
+        // SAFETY: `termios` is valid for writes of a `termios`, and is only
+        // read once `tcgetattr` has filled it in.
+        unsafe {
+            check(libc::tcgetattr(fd, termios.as_mut_ptr()))?;
+            Ok(termios.assume_init())
+        }
+    }
+
+    fn set_attributes(fd: RawFd, termios: &libc::termios) -> io::Result<()> {
+        // SAFETY: `termios` is a valid `termios`.
+        check(unsafe { libc::tcsetattr(fd, libc::TCSANOW, termios) })
+    }
+
+    /// Puts every terminal back the way it was before the first guard on
+    /// it, by restoring the guards from the newest to the oldest.
+    fn restore_all() {
+        let state = STATE.lock().unwrap_or_else(PoisonError::into_inner);
+        for (_, fd, termios) in state.saved.iter().rev() {
+            let _ = set_attributes(*fd, termios);
+        }
+    }
+
+    /// Installs the panic hook restoring the terminals, once per process.
+    fn install_panic_hook() {
+        static INSTALL: Once = Once::new();
+
+        INSTALL.call_once(|| {
+            let previous = std::panic::take_hook();
+            std::panic::set_hook(Box::new(move |info| {
+                // Restored before the message is printed, so that it isn't
+                // mangled by raw mode.
+                restore_all();
+                previous(info);
+            }));
+        });
+    }
+
+    /// Guard which keeps a terminal in a [`Mode`] while it's alive, putting
+    /// back the attributes it had before when it's dropped.
+    /// 
+    /// The terminal is also put back if the program panics, before the
+    /// panic message is printed, and if it gets `SIGINT` or `SIGTERM`,
+    /// after which the signal is handled as it would have been without the
+    /// guard, by ending the program or by the handler the program installed
+    /// before. The terminals are only put back by the signals while a guard
+    /// is alive.
+    /// 
+    /// ---
+    /// ## Examples
+    /// Reading single key presses:
+    /// ```no_run
+    /// use std::io::Read;
+    /// use utils::ansi::terminal;
+    /// 
+    /// let _guard = terminal::cbreak_mode()?;
+    /// let mut key = [0];
+    /// std::io::stdin().read_exact(&mut key)?;
+    /// println!("You pressed {:?}", key[0] as char);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[derive(Debug)]
+    #[must_use = "the terminal is put back as soon as the guard is dropped"]
+    pub struct ModeGuard {
+        id: u64,
+    }
+
+    impl ModeGuard {
+        /// Puts the terminal of `fd` in `mode`. An error is returned if it
+        /// isn't a terminal.
+        /// 
+        /// *Note: `fd` should stay open while the guard is alive, otherwise
+        /// its terminal can't be put back.*
+        pub fn new(fd: &impl AsRawFd, mode: Mode) -> io::Result<Self> {
+            static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+            let fd = fd.as_raw_fd();
+            let original = get_attributes(fd)?;
+
+            let mut termios = original;
+            match mode {
+                // SAFETY: `termios` is a valid `termios`.
+                Mode::Raw => unsafe { libc::cfmakeraw(&mut termios) },
+                Mode::Cbreak => termios.c_lflag &= !(libc::ICANON | libc::ECHO),
+            }
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+
+            install_panic_hook();
+            let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+            {
+                let mut state = STATE.lock().unwrap_or_else(PoisonError::into_inner);
+                state.saved.push((id, fd, original));
+                register(&mut state);
+            }
+
+            let guard = Self { id };
+            set_attributes(fd, &termios)?;
+            Ok(guard)
+        }
+    }
+
+    impl Drop for ModeGuard {
+        fn drop(&mut self) {
+            let mut state = STATE.lock().unwrap_or_else(PoisonError::into_inner);
+            let Some(index) = state.saved.iter().position(|&(id, ..)| id == self.id) else {
+                return;
+            };
+
+            let (_, fd, original) = state.saved.remove(index);
+            match state.saved[index..].iter_mut().find(|(_, other, _)| *other == fd) {
+                // A newer guard on the same terminal is still alive, which
+                // puts it back the way it was before this one instead.
+                Some((_, _, newer)) => *newer = original,
+                // Errors can't be returned here, and the terminal is gone
+                // if the attributes can't be set.
+                None => {
+                    let _ = set_attributes(fd, &original);
+                },
+            }
+
+            register(&mut state);
+        }
+    }
+
+    /// Puts the terminal of the stdin in [`Mode::Raw`], see [`ModeGuard`].
+    pub fn raw_mode() -> io::Result<ModeGuard> {
+        ModeGuard::new(&io::stdin(), Mode::Raw)
+    }
+
+    /// Puts the terminal of the stdin in [`Mode::Cbreak`], see
+    /// [`ModeGuard`].
+    pub fn cbreak_mode() -> io::Result<ModeGuard> {
+        ModeGuard::new(&io::stdin(), Mode::Cbreak)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::os::unix::process::ExitStatusExt;
+        use std::sync::atomic::AtomicBool;
+
+        use crate::ansi::terminal::testing::{alone, pty, run_alone};
+
+        static TERMINATED: AtomicBool = AtomicBool::new(false);
+
+        extern "C" fn on_terminate(_: libc::c_int) {
+            TERMINATED.store(true, Ordering::SeqCst);
+        }
+
+        #[test]
+        fn guards_put_the_terminal_back() {
+            if !alone() {
+                let output = run_alone(module_path!(), "guards_put_the_terminal_back");
+                assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+                return;
+            }
+
+            let (_leader, follower) = pty();
+            let echo = || get_attributes(follower.as_raw_fd()).unwrap().c_lflag & libc::ECHO != 0;
+            assert!(echo());
+            {
+                let _guard = ModeGuard::new(&follower, Mode::Raw).unwrap();
+                assert!(!echo());
+            }
+            assert!(echo());
+        }
+
+        #[test]
+        fn signals_put_the_terminal_back() {
+            if !alone() {
+                let output = run_alone(module_path!(), "signals_put_the_terminal_back");
+                assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+                return;
+            }
+
+            let (_leader, follower) = pty();
+            let on_terminate: extern "C" fn(libc::c_int) = on_terminate;
+            unsafe { libc::signal(libc::SIGTERM, on_terminate as libc::sighandler_t) };
+
+            let echo = |fd| get_attributes(fd).unwrap().c_lflag & libc::ECHO != 0;
+            assert!(echo(follower.as_raw_fd()));
+
+            // The terminal is put back, and then the handler from before
+            // the guard is called instead of ending the program.
+            let guard = ModeGuard::new(&follower, Mode::Cbreak).unwrap();
+            assert!(!echo(follower.as_raw_fd()));
+            unsafe { libc::raise(libc::SIGTERM) };
+            assert!(TERMINATED.swap(false, Ordering::SeqCst));
+            assert!(echo(follower.as_raw_fd()));
+
+            // Without a guard the terminal is left as it is.
+            drop(guard);
+            let mut termios = get_attributes(follower.as_raw_fd()).unwrap();
+            termios.c_lflag &= !libc::ECHO;
+            set_attributes(follower.as_raw_fd(), &termios).unwrap();
+            unsafe { libc::raise(libc::SIGTERM) };
+            assert!(TERMINATED.load(Ordering::SeqCst));
+            assert!(!echo(follower.as_raw_fd()));
+        }
+
+        #[test]
+        fn signals_end_the_program_after_the_guards() {
+            if !alone() {
+                let output = run_alone(module_path!(), "signals_end_the_program_after_the_guards");
+                assert_eq!(output.status.signal(), Some(libc::SIGINT), "{}", String::from_utf8_lossy(&output.stdout));
+                return;
+            }
+
+            let (_leader, follower) = pty();
+            drop(ModeGuard::new(&follower, Mode::Raw).unwrap());
+            unsafe { libc::raise(libc::SIGINT) };
+        }
+    }
+}
+
+#[cfg(unix)]
+mod resize {
+    use std::io;
+    use std::os::fd::{AsRawFd, RawFd};
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use signal_hook::iterator::{Handle, Signals};
+
+    use super::{size, Size};
+
+    /// The size of the terminal of `fd`, read with `TIOCGWINSZ`. An error
+    /// is returned if it isn't a terminal or its size isn't known.
+    /// 
+    /// ---
+    /// ## Examples
+    /// ```no_run
+    /// use utils::ansi::terminal;
+    /// 
+    /// let size = terminal::size_of(&std::io::stderr())?;
+    /// eprintln!("{}x{}", size.columns, size.lines);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn size_of(fd: &impl AsRawFd) -> io::Result<Size> {
+        let mut winsize = libc::winsize { ws_row: 0, ws_col: 0, ws_xpixel: 0, ws_ypixel: 0 };
+        // SAFETY: `TIOCGWINSZ` writes a `winsize`, which `winsize` is.
+        if unsafe { libc::ioctl(fd.as_raw_fd(), libc::TIOCGWINSZ, &mut winsize) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        if winsize.ws_col == 0 || winsize.ws_row == 0 {
+            return Err(io::Error::other("the size of the terminal isn't known"));
+        }
+
+        Ok(Size { columns: winsize.ws_col as usize, lines: winsize.ws_row as usize })
+    }
+
+    /// Subscription to the resizes of the terminal, which stops calling
+    /// its callback once dropped.
+    #[derive(Debug)]
+    #[must_use = "the subscription stops as soon as it's dropped"]
+    pub struct ResizeSubscription {
+        handle: Handle,
+    }
+
+    impl Drop for ResizeSubscription {
+        fn drop(&mut self) {
+            self.handle.close();
+        }
+    }
+
+    /// Calls `f` with the size from `query` on every `SIGWINCH`.
+    fn subscribe(
+        query: impl Fn() -> Option<Size> + Send + 'static,
+        mut f: impl FnMut(Size) + Send + 'static,
+    ) -> io::Result<ResizeSubscription> {
+        let mut signals = Signals::new([libc::SIGWINCH])?;
+        let handle = signals.handle();
+
+        std::thread::spawn(move || {
+            for _ in signals.forever() {
+                if let Some(size) = query() {
+                    f(size);
+                }
+            }
+        });
+
+        Ok(ResizeSubscription { handle })
+    }
+
+    /// Calls `f` with the new [`size`] of the terminal each time it's
+    /// resized, on a thread of its own, until the returned subscription is
+    /// dropped. Resizes are found through `SIGWINCH`, which is sent when the
+    /// terminal the program runs in is resized.
+    /// 
+    /// ---
+    /// ## Examples
+    /// ```no_run
+    /// use utils::ansi::terminal;
+    /// 
+    /// let _subscription = terminal::on_resize(|size| {
+    ///     eprintln!("Resized to {}x{}", size.columns, size.lines);
+    /// })?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn on_resize(f: impl FnMut(Size) + Send + 'static) -> io::Result<ResizeSubscription> {
+        subscribe(size, f)
+    }
+
+    /// Version of [`on_resize`] which calls `f` with the size of the
+    /// terminal of `fd`.
+    /// 
+    /// *Note: `fd` should stay open while the subscription is alive,
+    /// otherwise its size can't be read.*
+    /// 
+    /// ---
+    /// ## Examples
+    /// Following the terminal the program runs in, even when its output
+    /// is redirected:
+    /// ```no_run
+    /// use std::fs::File;
+    /// use utils::ansi::terminal;
+    /// 
+    /// let tty = File::open("/dev/tty")?;
+    /// let _subscription = terminal::on_resize_of(&tty, |size| {
+    ///     eprintln!("Resized to {}x{}", size.columns, size.lines);
+    /// })?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn on_resize_of(fd: &impl AsRawFd, f: impl FnMut(Size) + Send + 'static) -> io::Result<ResizeSubscription> {
+        let fd: RawFd = fd.as_raw_fd();
+        subscribe(move || size_of(&fd).ok(), f)
+    }
+
+    /// The new sizes of the terminal each time it's resized, delivered over
+    /// a channel, see [`on_resize`]. Iterating waits for the next resize.
+    /// 
+    /// ---
+    /// ## Examples
+    /// Redrawing between bits of work:
+    /// ```no_run
+    /// use utils::ansi::terminal;
+    /// 
+    /// let resizes = terminal::resizes()?;
+    /// loop {
+    ///     if let Some(size) = resizes.try_recv() {
+    ///         println!("Redrawing at {}x{}", size.columns, size.lines);
+    ///     }
+    ///     // ...
+    /// }
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[derive(Debug)]
+    pub struct Resizes {
+        receiver: mpsc::Receiver<Size>,
+        _subscription: ResizeSubscription,
+    }
+
+    impl Resizes {
+        /// The size of the latest resize which hasn't been received, if
+        /// there is one, without waiting. Earlier ones are skipped.
+        pub fn try_recv(&self) -> Option<Size> {
+            self.receiver.try_iter().last()
+        }
+
+        /// Waits up to `timeout` for the next resize.
+        pub fn recv_timeout(&self, timeout: Duration) -> Option<Size> {
+            self.receiver.recv_timeout(timeout).ok()
+        }
+    }
+
+    impl Iterator for Resizes {
+        type Item = Size;
+
+        fn next(&mut self) -> Option<Size> {
+            self.receiver.recv().ok()
+        }
+    }
+
+    /// The resizes of the terminal over a channel, see [`Resizes`].
+    pub fn resizes() -> io::Result<Resizes> {
+        let (sender, receiver) = mpsc::channel();
+        let subscription = on_resize(move |size| {
+            let _ = sender.send(size);
+        })?;
+
+        Ok(Resizes { receiver, _subscription: subscription })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        use crate::ansi::terminal::testing::{alone, pty, run_alone};
+
+        /// Sets the size of the pseudo terminal of `leader`.
+        fn resize(leader: &impl AsRawFd, columns: u16, lines: u16) {
+            let winsize = libc::winsize { ws_row: lines, ws_col: columns, ws_xpixel: 0, ws_ypixel: 0 };
+            assert_eq!(unsafe { libc::ioctl(leader.as_raw_fd(), libc::TIOCSWINSZ, &winsize) }, 0);
+        }
+
+        #[test]
+        fn sizes() {
+            let (leader, follower) = pty();
+            assert!(size_of(&follower).is_err());
+
+            resize(&leader, 100, 24);
+            assert_eq!(size_of(&follower).unwrap(), Size { columns: 100, lines: 24 });
+        }
+
+        #[test]
+        fn resizes_of_a_terminal() {
+            if !alone() {
+                let output = run_alone(module_path!(), "resizes_of_a_terminal");
+                assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+                return;
+            }
+
+            let (leader, follower) = pty();
+            let (sender, receiver) = mpsc::channel();
+            let _subscription = on_resize_of(&follower, move |size| sender.send(size).unwrap()).unwrap();
+
+            // The `SIGWINCH` the terminal would send is sent by hand.
+            resize(&leader, 120, 30);
+            unsafe { libc::raise(libc::SIGWINCH) };
+
+            let size = receiver.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(size, Size { columns: 120, lines: 30 });
+        }
+    }
+}
+
+/// Pseudo terminals for the tests reading from and writing to terminals,
+/// and processes of their own for the tests changing signal handlers.
+#[cfg(all(test, unix))]
+pub(crate) mod testing {
+    use std::fs::File;
+    use std::os::fd::FromRawFd;
+    use std::process::{Command, Output};
+    use std::ptr;
+
+    /// Set in the process [`run_alone`] starts.
+    const ALONE: &str = "UTILS_TEST_ALONE";
+
+    /// Whether this is the process [`run_alone`] started.
+    pub fn alone() -> bool {
+        std::env::var_os(ALONE).is_some()
+    }
+
+    /// Runs the test `name` of `module`, the [`module_path!`] of the test,
+    /// in a process of its own, as the signal handlers are shared by every
+    /// test in this one.
+    pub fn run_alone(module: &str, name: &str) -> Output {
+        let (_, module) = module.split_once("::").unwrap();
+        Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", &format!("{module}::{name}"), "--nocapture"])
+            .env(ALONE, name)
+            .output()
+            .unwrap()
+    }
+
+    /// Both ends of a new pseudo terminal.
+    pub fn pty() -> (File, File) {
+        let (mut leader, mut follower) = (0, 0);
+        let (name, termp, winp) = (ptr::null_mut(), ptr::null(), ptr::null());
+        assert_eq!(unsafe { libc::openpty(&mut leader, &mut follower, name, termp, winp) }, 0);
+        unsafe { (File::from_raw_fd(leader), File::from_raw_fd(follower)) }
+    }
+}
@@ -1,5 +1,9 @@
 #[cfg(feature = "derive")]
 pub use util_derive::{Add, Sub, Mul, Div, PartialOps};
+#[cfg(feature = "ansi")]
+pub use util_derive::{
+    colprint, colprintln, ecolprint, ecolprintln, colformat, colwrite, colwriteln
+};
 #[cfg(feature = "timeprint")]
 pub use chrono;
 
@@ -201,32 +205,96 @@ macro_rules! timeprintln {
 /// ## Style codes
 /// | Name | String | Meaning |
 /// | ---- | ------ | ------- |
-/// | [`X`] | `"_X"` | Clears all ANSI formatting |
-/// | [`style::BLD`] | `"_BLD"` | Bold |
-/// | [`style::ITL`] | `"_ITL"` | Italicized |
-/// | [`style::UND`] | `"_UND"` | Underline |
+/// | [`X`](ansi::X) | `"_X"` | Clears all ANSI formatting |
+/// | [`BLD`](ansi::BLD) | `"_BLD"` | Bold |
+/// | [`ITL`](ansi::ITL) | `"_ITL"` | Italicized |
+/// | [`UND`](ansi::UND) | `"_UND"` | Underline |
+/// | [`DIM`](ansi::DIM) | `"_DIM"` | Dim |
+/// | [`BLN`](ansi::BLN) | `"_BLN"` | Blink |
+/// | [`REV`](ansi::REV) | `"_REV"` | Reverse |
+/// | [`HID`](ansi::HID) | `"_HID"` | Hidden |
+/// | [`STK`](ansi::STK) | `"_STK"` | Strikethrough |
+/// | [`DUN`](ansi::DUN) | `"_DUN"` | Double underline |
+/// | [`OVL`](ansi::OVL) | `"_OVL"` | Overline |
+/// ---
+/// ## Reset codes
+/// Unlike [`X`](ansi::X) these only clear one style or colour, leaving
+/// the rest as they are.
+/// 
+/// | Name | String | Meaning |
+/// | ---- | ------ | ------- |
+/// | [`NBLD`](ansi::NBLD) | `"_NBLD"` | Not bold or dim |
+/// | [`NITL`](ansi::NITL) | `"_NITL"` | Not italicized |
+/// | [`NUND`](ansi::NUND) | `"_NUND"` | Not underlined or double underlined |
+/// | [`NBLN`](ansi::NBLN) | `"_NBLN"` | Not blinking |
+/// | [`NREV`](ansi::NREV) | `"_NREV"` | Not reversed |
+/// | [`NHID`](ansi::NHID) | `"_NHID"` | Not hidden |
+/// | [`NSTK`](ansi::NSTK) | `"_NSTK"` | Not strikethrough |
+/// | [`NOVL`](ansi::NOVL) | `"_NOVL"` | Not overlined |
+/// | [`DEF`](ansi::DEF) | `".DEF"` | Default foreground color |
+/// | [`BDEF`](ansi::BDEF) | `"#DEF"` | Default background color |
 /// ---
 /// ## Color codes
+/// The constants and the markup strings name colours differently. In a
+/// constant a `B` in front means background and `BR` means bright, so
+/// [`BRED`](ansi::BRED) is the background red of `"#RED"` and
+/// [`BRRED`](ansi::BRRED) is the bright red text of `".BRED"`. In a string
+/// the sigil picks the text (`.`) or the background (`#`) and a `B` in
+/// front of the name means bright, so `"#BRED"` is
+/// [`BBRRED`](ansi::BBRRED), bright red background.
+/// 
+/// | Foreground | String | Background | String | Meaning |
+/// | ---------- | ------ | ---------- | ------ | ------- |
+/// | [`BLK`](ansi::BLK) | ".BLK" | [`BBLK`](ansi::BBLK) | "#BLK" | Black |
+/// | [`RED`](ansi::RED) | ".RED" | [`BRED`](ansi::BRED) | "#RED" | Red |
+/// | [`GRN`](ansi::GRN) | ".GRN" | [`BGRN`](ansi::BGRN) | "#GRN" | Green |
+/// | [`YLW`](ansi::YLW) | ".YLW" | [`BYLW`](ansi::BYLW) | "#YLW" | Yellow |
+/// | [`BLU`](ansi::BLU) | ".BLU" | [`BBLU`](ansi::BBLU) | "#BLU" | Blue |
+/// | [`MGT`](ansi::MGT) | ".MGT" | [`BMGT`](ansi::BMGT) | "#MGT" | Magenta |
+/// | [`CYN`](ansi::CYN) | ".CYN" | [`BCYN`](ansi::BCYN) | "#CYN" | Cyan |
+/// | [`WHT`](ansi::WHT) | ".WHT" | [`BWHT`](ansi::BWHT) | "#WHT" | White |
+/// ---
+/// ## Bright color codes
 /// | Foreground | String | Background | String | Meaning |
 /// | ---------- | ------ | ---------- | ------ | ------- |
-/// | [`f_color::BLK`] | ".BLK" | [`b_color::BLK`] | "#BLK" | Black |
-/// | [`f_color::RED`] | ".RED" | [`b_color::RED`] | "#RED" | Red |
-/// | [`f_color::GRN`] | ".GRN" | [`b_color::GRN`] | "#GRN" | Green |
-/// | [`f_color::YLW`] | ".YLW" | [`b_color::YLW`] | "#YLW" | Yellow |
-/// | [`f_color::BLU`] | ".BLU" | [`b_color::BLU`] | "#BLU" | Blue |
-/// | [`f_color::MGT`] | ".MGT" | [`b_color::MGT`] | "#MGT" | Magenta |
-/// | [`f_color::CYN`] | ".CYN" | [`b_color::CYN`] | "#CYN" | Cyan |
-/// | [`f_color::WHT`] | ".WHT" | [`b_color::WHT`] | "#WHT" | White |
+/// | [`BRBLK`](ansi::BRBLK) | ".BBLK" | [`BBRBLK`](ansi::BBRBLK) | "#BBLK" | Bright Black |
+/// | [`BRRED`](ansi::BRRED) | ".BRED" | [`BBRRED`](ansi::BBRRED) | "#BRED" | Bright Red |
+/// | [`BRGRN`](ansi::BRGRN) | ".BGRN" | [`BBRGRN`](ansi::BBRGRN) | "#BGRN" | Bright Green |
+/// | [`BRYLW`](ansi::BRYLW) | ".BYLW" | [`BBRYLW`](ansi::BBRYLW) | "#BYLW" | Bright Yellow |
+/// | [`BRBLU`](ansi::BRBLU) | ".BBLU" | [`BBRBLU`](ansi::BBRBLU) | "#BBLU" | Bright Blue |
+/// | [`BRMGT`](ansi::BRMGT) | ".BMGT" | [`BBRMGT`](ansi::BBRMGT) | "#BMGT" | Bright Magenta |
+/// | [`BRCYN`](ansi::BRCYN) | ".BCYN" | [`BBRCYN`](ansi::BBRCYN) | "#BCYN" | Bright Cyan |
+/// | [`BRWHT`](ansi::BRWHT) | ".BWHT" | [`BBRWHT`](ansi::BBRWHT) | "#BWHT" | Bright White |
+/// ---
+/// ## 256 color codes
+/// | Foreground | String | Background | String | Meaning |
+/// | ---------- | ------ | ---------- | ------ | ------- |
+/// | [`f_color::ansi256`](ansi::f_color::ansi256) | ".C0" to ".C255" | [`b_color::ansi256`](ansi::b_color::ansi256) | "#C0" to "#C255" | Color of the [256 color palette](ansi::palette) |
+/// ---
+/// ## Hex and CSS color codes
+/// | Foreground | String | Background | String | Meaning |
+/// | ---------- | ------ | ---------- | ------ | ------- |
+/// | [`f_color::rgb`](ansi::f_color::rgb) | ".#ff8800" or ".(#ff8800)" | [`b_color::rgb`](ansi::b_color::rgb) | "#(#ff8800)" | Hex RGB color, also "#f80" in brackets |
+/// | [`f_color::rgb`](ansi::f_color::rgb) | ".orange" or ".(orange)" | [`b_color::rgb`](ansi::b_color::rgb) | "#orange" or "#(orange)" | One of the [CSS colors](ansi::palette::CSS_COLORS) |
 /// ---
 /// # Examples
 /// ## Using consts directly
 /// ```
-/// use utils::ansi::{style::*, f_color::*, *};
+/// use utils::ansi::*;
 /// 
 /// let manual = String::from("\x1b[1m\x1b[3m\x1b[32mHello, world!\x1b[0m");
 /// let output = format!("{BLD}{ITL}{GRN}Hello, world!{X}");
 /// assert_eq!(output, manual);
 /// ```
+/// ## Targeted resets
+/// ```
+/// use utils::ansi::*;
+/// 
+/// // Only the bold and the red are cleared, the blue background stays.
+/// let output = format!("{BBLU}{BLD}{RED}Error:{NBLD}{DEF} not found{X}");
+/// assert_eq!(output, "\x1b[44m\x1b[1m\x1b[31mError:\x1b[22m\x1b[39m not found\x1b[0m");
+/// assert_eq!(output, replace_cc(String::from("#BLU_BLD.REDError:_NBLD.DEF not found_X")));
+/// ```
 #[cfg(feature = "ansi")]
 pub mod ansi;
 
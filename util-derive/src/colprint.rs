@@ -0,0 +1,570 @@
+use proc_macro2::TokenStream as Tokens;
+use syn::parse::{Parse, ParseStream};
+
+use util_markup::codes::REPLACE_MAP;
+use util_markup::palette::{ColorDepth, CSS_COLORS};
+use util_markup::tags::{parse_tag, parse_tag_color, Markup, COLORS, STYLES};
+use util_markup::tokens::{match_code, Token};
+
+/// The input to the `colprint!` family of macros, a format string literal
+/// followed by the rest of the arguments which are passed through as is.
+pub struct ColArgs {
+    fmt: Option<syn::LitStr>,
+    rest: Tokens,
+}
+
+impl Parse for ColArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(Self { fmt: None, rest: Tokens::new() });
+        }
+
+        let fmt = input.parse().map_err(|e| {
+            syn::Error::new(e.span(), "format argument must be a string literal")
+        })?;
+
+        Ok(Self { fmt: Some(fmt), rest: input.parse()? })
+    }
+}
+
+/// The input to the `colwrite!` macros, a destination to write to followed
+/// by the same arguments as the `colprint!` macros.
+pub struct ColWriteArgs {
+    dst: syn::Expr,
+    args: ColArgs,
+}
+
+impl Parse for ColWriteArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let dst = input.parse()?;
+
+        if input.is_empty() {
+            return Ok(Self { dst, args: ColArgs { fmt: None, rest: Tokens::new() } });
+        }
+
+        input.parse::<syn::Token![,]>()?;
+
+        Ok(Self { dst, args: input.parse()? })
+    }
+}
+
+/// Finds the next `{...}` placeholder in a format string, skipping over
+/// escaped `{{` braces, and returns its byte range.
+fn next_placeholder(s: &str) -> Option<(usize, usize)> {
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if c != '{' {
+            continue;
+        }
+
+        if let Some((_, '{')) = chars.peek() {
+            chars.next();
+            continue;
+        }
+
+        let end = s[i..].find('}').map_or(s.len(), |j| i + j + 1);
+        return Some((i, end));
+    }
+
+    None
+}
+
+/// Number of single character edits to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<_> = b.chars().collect();
+    let mut row: Vec<_> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = (prev + (ca != *cb) as usize).min(row[j] + 1).min(cur + 1);
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+/// Points at `range` of the format string if the compiler allows it,
+/// otherwise at the whole literal.
+fn span_of(lit: &syn::LitStr, range: std::ops::Range<usize>) -> Option<proc_macro2::Span> {
+    let src = lit.token().to_string();
+
+    if src.len() == lit.value().len() + 2 && src[1..src.len() - 1] == lit.value() {
+        return lit.token().subspan(range.start + 1..range.end + 1);
+    }
+
+    None
+}
+
+/// An error with `message` pointing at `range` of the format string. Only
+/// nightly compilers can point inside a literal, so otherwise the error
+/// points at the whole literal and the message shows the line of the
+/// format string holding `range`, marked under it.
+fn error_at(lit: &syn::LitStr, range: std::ops::Range<usize>, message: String) -> syn::Error {
+    if let Some(span) = span_of(lit, range.clone()) {
+        return syn::Error::new(span, message);
+    }
+
+    let fmt = lit.value();
+    let start = fmt[..range.start].rfind('\n').map_or(0, |i| i + 1);
+    let end = fmt[range.start..].find('\n').map_or(fmt.len(), |i| range.start + i);
+    let (line, range) = (&fmt[start..end], range.start - start..range.end.min(end) - start);
+
+    let indent = " ".repeat(line[..range.start].chars().count());
+    let marker = "^".repeat(line[range].chars().count().max(1));
+    syn::Error::new(lit.span(), format!("{message}\n  {line}\n  {indent}{marker}"))
+}
+
+/// Adds `error` to the errors found so far.
+fn push_error(errors: &mut Option<syn::Error>, error: syn::Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// Checks the text of a format string for words which look like codes,
+/// but aren't. These are a code directly followed by more capitals, like
+/// `#CYNN`, a typo one letter off a code, like `.GRE`, a palette colour out
+/// of range, like `.C300`, and a bracketed colour which doesn't parse, like
+/// `.(ornage)`. Each of them is added to `errors`.
+/// 
+/// Sigils in the middle of a word, like the `_` of `CONFIG_DIR`, are only
+/// checked for a code followed by more capitals, like the `_HID` of
+/// `SHOW_HIDDEN`, as it would still be replaced, unless they directly
+/// follow a code.
+fn check_codes(lit: &syn::LitStr, text: &str, offset: usize, errors: &mut Option<syn::Error>) {
+    // End of the last tag or hex or CSS colour, whose `#` isn't a sigil of
+    // its own.
+    let mut covered = 0;
+    // End of the last code, which the next one can be chained to.
+    let mut code_end = 0;
+
+    for (i, sigil) in text.match_indices(['_', '.', '#', '[']) {
+        if i < covered {
+            continue;
+        }
+
+        if sigil == "[" {
+            let end = text[i..].find(']').map_or(i, |end| i + end);
+            let spec = &text[i + 1..end.max(i + 1)];
+            if !spec.contains('[') && (spec.starts_with('/') || parse_tag(spec).is_some()) {
+                covered = end;
+            }
+            continue;
+        }
+
+        if let Some((len, Token::Rgb { .. })) = match_code(&text[i..]) {
+            covered = i + len;
+            code_end = covered;
+            continue;
+        }
+
+        let before = &text[..i];
+        if before.ends_with('\\') && !before.ends_with("\\\\") {
+            continue;
+        }
+        let in_word = i != code_end && before.ends_with(|c: char| c.is_alphanumeric() || c == '_');
+
+        let after = &text[i + 1..];
+        if let Some(inner) = after.strip_prefix('(') {
+            if let Err(e) = check_bracketed(lit, text, offset, i, inner) {
+                push_error(errors, e);
+            }
+            continue;
+        }
+
+        let len = after
+            .find(|c: char| !c.is_ascii_uppercase() && !c.is_ascii_digit())
+            .unwrap_or(after.len());
+        if len == 0 || after[len..].starts_with(|c: char| c.is_alphanumeric()) {
+            continue;
+        }
+
+        let word = &text[i..i + 1 + len];
+        let matched = match_code(word).map_or(0, |(matched, _)| matched);
+        if matched == word.len() {
+            code_end = i + matched;
+            continue;
+        }
+
+        let range = offset + i..offset + i + word.len();
+
+        if matched > 0 {
+            let (code, rest) = word.split_at(matched);
+            push_error(errors, error_at(lit, range, format!(
+                "`{word}` reads as the code `{code}` followed by `{rest}`, escape it as \
+                `\\{word}` to print it as text or pass `{rest}` as an argument to keep the code"
+            )));
+            continue;
+        }
+
+        if in_word {
+            continue;
+        }
+
+        let digits = word.get(2..).unwrap_or_default();
+        if sigil != "_" && word[1..].starts_with('C') && !digits.is_empty()
+            && digits.bytes().all(|b| b.is_ascii_digit())
+        {
+            push_error(errors, error_at(lit, range, format!(
+                "`{word}` is not in the 256 colour palette, which goes from `{sigil}C0` to \
+                `{sigil}C255`, escape it as `\\{word}` to print it as text"
+            )));
+            continue;
+        }
+
+        let suggestion = REPLACE_MAP
+            .iter()
+            .filter(|(code, _)| code.starts_with(sigil))
+            .map(|(code, _)| (edit_distance(word, code), code))
+            .min();
+
+        if let Some((1, code)) = suggestion.filter(|_| len >= 3) {
+            push_error(errors, error_at(lit, range, format!(
+                "unknown code `{word}`, did you mean `{code}`? To print `{word}` as text escape \
+                it as `\\{word}`"
+            )));
+        }
+    }
+}
+
+/// Checks a bracketed colour, `.(...)` or `#(...)`, starting at `i`. Only
+/// brackets holding something meant as a colour are errors, a hex colour
+/// or a word close to a CSS colour name, so text like `end.(see above)` is
+/// left alone.
+fn check_bracketed(lit: &syn::LitStr, text: &str, offset: usize, i: usize, inner: &str) -> syn::Result<()> {
+    let Some(end) = inner.find(')') else {
+        return Ok(());
+    };
+    let word = &text[i..i + end + 3];
+    if match_code(word).is_some() {
+        return Ok(());
+    }
+
+    let color = &inner[..end];
+    let range = offset + i..offset + i + word.len();
+
+    if color.starts_with('#') {
+        return Err(error_at(lit, range, format!(
+            "`{word}` is not a hex colour, which is written as `#rrggbb` or `#rgb`"
+        )));
+    }
+
+    let suggestion = CSS_COLORS
+        .iter()
+        .map(|(name, _)| (edit_distance(color, name), name))
+        .min();
+
+    match suggestion {
+        Some((1..=2, name)) if color.len() >= 3 => Err(error_at(lit, range, format!(
+            "unknown colour `{color}`, did you mean `{name}`? To print `{word}` as text pass it \
+            as an argument"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Checks the opening tags in the text of a format string for a word one
+/// letter off a tag word, like `[grean]`. Anything else in brackets which isn't a
+/// tag is left alone, so text like `[INFO]` can still be printed.
+fn check_tags(lit: &syn::LitStr, text: &str, offset: usize) -> syn::Result<()> {
+    for (i, _) in text.match_indices('[') {
+        let Some(end) = text[i..].find(']').map(|end| i + end) else {
+            if text[i + 1..].starts_with("link=") {
+                return Err(error_at(lit, offset + i..offset + text.len(), "the url of a `[link=...]` tag can't hold a \
+                    placeholder, pass a link made by `utils::ansi::hyperlink` as an argument instead".to_string()));
+            }
+            break;
+        };
+        let before = &text[..i];
+        let (tag, spec) = (&text[i..=end], &text[i + 1..end]);
+        if before.ends_with('\\') && !before.ends_with("\\\\")
+            || spec.contains('[') || spec.starts_with('/') || parse_tag(spec).is_some()
+        {
+            continue;
+        }
+
+        let known = |word: &str| {
+            word == "on" || STYLES.iter().any(|(name, _)| *name == word)
+                || parse_tag_color(word, false).is_some()
+        };
+        let names = STYLES
+            .iter()
+            .map(|(name, _)| *name)
+            .chain(COLORS.iter().map(|(name, ..)| *name))
+            .chain(CSS_COLORS.iter().map(|(name, _)| *name));
+
+        for word in spec.split_whitespace().filter(|word| !known(word)) {
+            let suggestion = names.clone().map(|name| (edit_distance(word, name), name)).min();
+
+            if let Some((1, name)) = suggestion.filter(|_| word.len() >= 4) {
+                return Err(error_at(lit, offset + i..offset + end + 1, format!(
+                    "unknown word `{word}` in the tag `{tag}`, did you mean `{name}`? To print \
+                    it as text escape it as `\\{tag}`"
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A format string with its markup codes replaced, for each depth of
+/// colour and with the codes left out.
+#[derive(PartialEq)]
+pub struct Replaced {
+    plain: String,
+    ansi16: String,
+    ansi256: String,
+    truecolor: String,
+}
+
+impl Replaced {
+    /// Adds a token, writing the end of a link as ` (url)` unless `links`.
+    fn push(&mut self, token: Token, links: bool) {
+        match token {
+            Token::Text(text) => self.plain.push_str(text),
+            Token::Link(_) if !links => return,
+            Token::LinkEnd(url) if !links => return self.push(Token::Text(&format!(" ({url})")), links),
+            _ => (),
+        }
+        for (depth, out) in [
+            (ColorDepth::Ansi16, &mut self.ansi16),
+            (ColorDepth::Ansi256, &mut self.ansi256),
+            (ColorDepth::TrueColor, &mut self.truecolor),
+        ] {
+            token.write_at(depth, out).unwrap();
+        }
+    }
+}
+
+/// Checks the text of a format string for mistakes in its markup, see
+/// [`check_codes`] and [`check_tags`].
+pub fn check(lit: &syn::LitStr) -> syn::Result<()> {
+    let fmt = lit.value();
+    let mut errors = None;
+    let mut offset = 0;
+
+    loop {
+        let rest = &fmt[offset..];
+        let (text, placeholder) = match next_placeholder(rest) {
+            Some((start, end)) => (&rest[..start], &rest[start..end]),
+            None => (rest, ""),
+        };
+
+        check_codes(lit, text, offset, &mut errors);
+        if let Err(e) = check_tags(lit, text, offset) {
+            push_error(&mut errors, e);
+        }
+
+        offset += text.len() + placeholder.len();
+        if offset == fmt.len() {
+            break errors.map_or(Ok(()), Err);
+        }
+    }
+}
+
+/// Replaces the tags and markup codes in the text of a format string with
+/// their escape codes, leaving the `{...}` placeholders untouched. Links
+/// are written as hyperlinks if `links`, otherwise as `text (url)`.
+pub fn replace_codes(lit: &syn::LitStr, links: bool) -> syn::Result<Replaced> {
+    let fmt = lit.value();
+    let mut out = Replaced {
+        plain: String::with_capacity(fmt.len()),
+        ansi16: String::with_capacity(fmt.len()),
+        ansi256: String::with_capacity(fmt.len()),
+        truecolor: String::with_capacity(fmt.len()),
+    };
+    let mut markup = Markup::new();
+    let mut offset = 0;
+
+    loop {
+        let rest = &fmt[offset..];
+        let (text, placeholder) = match next_placeholder(rest) {
+            Some((start, end)) => (&rest[..start], &rest[start..end]),
+            None => (rest, ""),
+        };
+
+        markup.feed(text, |token| out.push(token, links));
+        if let Some(range) = markup.take_unmatched() {
+            let tag = &text[range.clone()];
+            return Err(error_at(lit, offset + range.start..offset + range.end, format!(
+                "`{tag}` doesn't close any open tag, escape it as `\\{tag}` to print it as text"
+            )));
+        }
+        for out in [&mut out.plain, &mut out.ansi16, &mut out.ansi256, &mut out.truecolor] {
+            out.push_str(placeholder);
+        }
+
+        offset += text.len() + placeholder.len();
+        if offset == fmt.len() {
+            markup.finish(|token| out.push(token, links));
+            break Ok(out);
+        }
+    }
+}
+
+pub fn colprint_macro(args: ColArgs, print: Tokens, stream: Tokens) -> Tokens {
+    expand(print, None, args, Some(stream))
+}
+
+pub fn colformat_macro(args: ColArgs) -> Tokens {
+    expand(quote!(format), None, args, None)
+}
+
+pub fn colwrite_macro(args: ColWriteArgs, write: Tokens) -> Tokens {
+    let ColWriteArgs { dst, args } = args;
+
+    expand(write, Some(dst), args, None)
+}
+
+/// Expands to `mac!(dst, fmt, args...)` with the codes in `fmt` replaced,
+/// or left out if colour is disabled for `stream`. Without a stream the
+/// codes are always written, and links are written like
+/// `utils::ansi::markup::render` does.
+fn expand(mac: Tokens, dst: Option<syn::Expr>, args: ColArgs, stream: Option<Tokens>) -> Tokens {
+    let ColArgs { fmt, rest } = args;
+    let dst = dst.map(|dst| quote!(#dst,));
+
+    let Some(fmt) = fmt else {
+        return quote!(#mac!(#dst ""));
+    };
+
+    match check(&fmt) {
+        Ok(()) => select(mac, dst, &fmt, rest, stream),
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+/// Expands to the call of `mac` for each output of `fmt`, selected by the
+/// policy of `stream`.
+fn select(mac: Tokens, dst: Option<Tokens>, fmt: &syn::LitStr, rest: Tokens, stream: Option<Tokens>) -> Tokens {
+    let replaced = replace_codes(fmt, true).and_then(|linked| Ok((linked, replace_codes(fmt, false)?)));
+    let (linked, replaced) = match replaced {
+        Ok(replaced) => replaced,
+        Err(e) => return e.to_compile_error(),
+    };
+    let call = |out: &str| {
+        let out = syn::LitStr::new(out, fmt.span());
+        quote!(#mac!(#dst #out #rest))
+    };
+
+    let policy = quote!(utils::ansi::policy);
+    let colored = |replaced: &Replaced| {
+        if replaced.ansi16 == replaced.truecolor {
+            return call(&replaced.truecolor);
+        }
+
+        let [ansi16, ansi256, truecolor] = [&replaced.ansi16, &replaced.ansi256, &replaced.truecolor].map(|out| call(out));
+        quote!(
+            match #policy::depth() {
+                #policy::ColorDepth::Ansi16 => #ansi16,
+                #policy::ColorDepth::Ansi256 => #ansi256,
+                #policy::ColorDepth::TrueColor => #truecolor,
+            }
+        )
+    };
+
+    let Some(stream) = stream else {
+        if linked != replaced {
+            let (linked, colored) = (colored(&linked), colored(&replaced));
+            return quote!(
+                if #policy::hyperlinks(#policy::Stream::Stdout) {
+                    #linked
+                } else {
+                    #colored
+                }
+            );
+        }
+
+        return colored(&replaced);
+    };
+
+    if linked != replaced {
+        let (linked, colored, plain) = (colored(&linked), colored(&replaced), call(&replaced.plain));
+        return quote!(
+            if #policy::hyperlinks(#policy::Stream::#stream) {
+                #linked
+            } else if #policy::enabled(#policy::Stream::#stream) {
+                #colored
+            } else {
+                #plain
+            }
+        );
+    }
+
+    // Only check the policy when it would change the output.
+    if replaced.plain == replaced.truecolor {
+        return call(&replaced.plain);
+    }
+
+    let (colored, plain) = (colored(&replaced), call(&replaced.plain));
+    quote!(
+        if #policy::enabled(#policy::Stream::#stream) {
+            #colored
+        } else {
+            #plain
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The messages of the errors in `fmt`.
+    fn errors(fmt: &str) -> Vec<String> {
+        let lit = syn::LitStr::new(fmt, proc_macro2::Span::call_site());
+        check(&lit).err().into_iter().flatten().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn sigils_inside_words() {
+        for fmt in ["Set CONFIG_DIR to {}", "ID_REG {}", "file.C300", "a.GRE", "Saved_X"] {
+            assert_eq!(errors(fmt), Vec::<String>::new(), "{fmt}");
+        }
+        // Still replaced, so not printed as is.
+        assert_eq!(errors("SHOW_HIDDEN=1").len(), 1);
+    }
+
+    #[test]
+    fn chained_codes() {
+        assert!(errors("#CYN.WHT_BLD_ITLHello, world!_X").is_empty());
+        assert!(errors(".#ff8800_BLDHi").is_empty());
+        assert_eq!(errors("#CYN.WHT_BLDD Hi").len(), 1);
+        assert_eq!(errors(".RED_ITK Hi").len(), 1);
+    }
+
+    #[test]
+    fn unknown_codes_are_errors() {
+        assert_eq!(errors(".GRE hi #CYNN there"), [
+            "unknown code `.GRE`, did you mean `.GRN`? To print `.GRE` as text escape it as \
+            `\\.GRE`\n  .GRE hi #CYNN there\n  ^^^^",
+            "`#CYNN` reads as the code `#CYN` followed by `N`, escape it as `\\#CYNN` to print it \
+            as text or pass `N` as an argument to keep the code\n  .GRE hi #CYNN there\n          ^^^^^",
+        ]);
+        assert_eq!(errors("see #REDIS").len(), 1);
+        assert_eq!(errors("use _XML and .C300").len(), 2);
+        assert!(errors(r"\#REDIS and \.GRE").is_empty());
+    }
+
+    #[test]
+    fn errors_mark_their_line() {
+        let errors = errors("first line\nthen «.GRE» {}\n");
+        assert!(errors[0].ends_with("\n  then «.GRE» {}\n        ^^^^"), "{errors:?}");
+    }
+
+    #[test]
+    fn malformed_markup_is_an_error() {
+        assert!(errors(".(ornage)Hello")[0].contains("did you mean `orange`"));
+        assert_eq!(errors("#(#ggg)Hello").len(), 1);
+        assert!(errors("[grean]Hello")[0].contains("did you mean `green`"));
+        assert!(errors("[INFO] end.(see above)").is_empty());
+    }
+}
@@ -6,6 +6,8 @@ extern crate syn;
 extern crate quote;
 use proc_macro2::TokenStream as Tokens;
 
+mod colprint;
+
 fn derive_macro(ast: syn::DeriveInput, operation_name: Tokens, operation: Tokens) -> Tokens {
     let name = ast.ident;
     let fields = if let syn::Data::Struct(f) = ast.data {
@@ -332,4 +334,324 @@ pub fn partial_ops(input: TokenStream) -> TokenStream {
             #div
         }
     )
-}
\ No newline at end of file
+}
+
+/// Macro which replaces the custom codes in its format string with ansi
+/// color escape codes at compile time and [`print!`]s it to the stdout.
+/// 
+/// Only the format string literal is read as markup, the values of any
+/// arguments are printed verbatim. So a value containing `#RED` or `_X`
+/// can never change the colours of the terminal.
+/// 
+/// The codes are left out when colour is disabled by
+/// `utils::ansi::policy`, e.g. when the stdout is not a terminal.
+/// 
+/// ---
+/// # Examples
+/// ```
+/// use utils::colprint;
+/// 
+/// colprint!("#CYN.WHT_BLD_ITLHello, world!_X");
+/// 
+/// let file = "report.RED_X.txt";
+/// colprint!(".GRNSaved_X {file}");
+/// ```
+/// ---
+/// # Errors
+/// The format string is checked at compile time for words which look
+/// like codes but aren't, which would otherwise be printed as is. Each
+/// error points at the code on nightly compilers, elsewhere it points at
+/// the format string and marks the code under a copy of its line.
+/// 
+/// A typo one letter off a code is an error, suggesting the code:
+/// ```compile_fail
+/// use utils::colprint;
+/// 
+/// colprint!(".GRE Hello"); // did you mean `.GRN`?
+/// ```
+/// As is a code directly followed by more capitals, which would read as
+/// the code followed by text:
+/// ```compile_fail
+/// use utils::colprint;
+/// 
+/// colprint!("#CYNN Hello"); // `#CYN` followed by `N`
+/// ```
+/// Sigils in the middle of a word aren't checked for typos unless they
+/// follow a code, so text like `CONFIG_DIR` is printed as is. A code in
+/// the middle of a word is still replaced though, so `SHOW_HIDDEN` is an
+/// error as it reads as `_HID` followed by `DEN`.
+/// 
+/// And so is a bracketed colour which isn't one:
+/// ```compile_fail
+/// use utils::colprint;
+/// 
+/// colprint!(".(ornage)Hello"); // did you mean `orange`?
+/// ```
+/// As are a misspelled word in a tag and a closing tag with nothing to
+/// close:
+/// ```compile_fail
+/// use utils::colprint;
+/// 
+/// colprint!("[grean]Hello"); // did you mean `green`?
+/// ```
+/// ```compile_fail
+/// use utils::colprint;
+/// 
+/// colprint!("Hello[/red]");
+/// ```
+/// Text which is meant to look like this can be escaped or passed as an
+/// argument:
+/// ```
+/// use utils::colprint;
+/// 
+/// colprint!(r"\#CYNN is printed as text, as is {}", ".GRE");
+/// ```
+/// ---
+/// # Tags
+/// Scoped tags, `[bold on blue]...[/]`, go back to the style around them
+/// when closed and can be used alongside the codes, see
+/// `utils::ansi::markup` for the words they can hold.
+/// ```
+/// use utils::colprint;
+/// 
+/// let file = "main.rs";
+/// colprint!("[bold on blue]Error in [red]{file}[/red] at line 3[/]");
+/// ```
+/// Words in square brackets which make a valid tag are always read as a
+/// tag, so text like `[default]` or `[bold]` in a format string written
+/// before tags existed now changes the style. Escape it with a backslash
+/// to print it as is. Brackets which aren't a tag, like `[INFO]`, are
+/// printed as they are.
+/// ```
+/// use utils::colformat;
+/// 
+/// assert_eq!(colformat!("[default] options [/]"), "\x1b[39m options \x1b[0m");
+/// assert_eq!(colformat!(r"\[default] options \[/] [INFO]"), "[default] options [/] [INFO]");
+/// ```
+/// Links are written as OSC 8 hyperlinks, or as `text (url)` where
+/// `utils::ansi::policy::hyperlinks` says they aren't supported. The url
+/// can't hold a placeholder, use `utils::ansi::hyperlink` for those.
+/// ```
+/// use utils::colprint;
+/// 
+/// colprint!("See [link=https://example.com/docs]the docs[/link] for more");
+/// ```
+/// ---
+/// # Codes
+/// Codes are matched and escaped as described in `utils::ansi::replace_cc`,
+/// e.g. `\_X` prints `_X` literally.
+/// Colours the terminal can't show are turned into the closest colour it
+/// can, see `utils::ansi::policy::depth`.
+/// 
+/// | Text | To  | Color / Style |
+/// | ---- | --- | ------------- |
+/// | `_X` | \x1b[0m | Clear ANSI formatting |
+/// | `_BLD` | `\x1b[1m` | Bold |
+/// | `_ITL` | `\x1b[3m` | Italicized |
+/// | `_UND` | `\x1b[4m` | Underline |
+/// | `_DIM` | `\x1b[2m` | Dim |
+/// | `_BLN` | `\x1b[5m` | Blink |
+/// | `_REV` | `\x1b[7m` | Reverse |
+/// | `_HID` | `\x1b[8m` | Hidden |
+/// | `_STK` | `\x1b[9m` | Strikethrough |
+/// | `_DUN` | `\x1b[21m` | Double underline |
+/// | `_OVL` | `\x1b[53m` | Overline |
+/// | `_NBLD` | `\x1b[22m` | Not bold or dim |
+/// | `_NITL` | `\x1b[23m` | Not italicized |
+/// | `_NUND` | `\x1b[24m` | Not underlined or double underlined |
+/// | `_NBLN` | `\x1b[25m` | Not blinking |
+/// | `_NREV` | `\x1b[27m` | Not reversed |
+/// | `_NHID` | `\x1b[28m` | Not hidden |
+/// | `_NSTK` | `\x1b[29m` | Not strikethrough |
+/// | `_NOVL` | `\x1b[55m` | Not overlined |
+/// | `.DEF` | `\x1b[39m` | Text Default Color |
+/// | `#DEF` | `\x1b[49m` | Background Default Color |
+/// | `.BLK` | `\x1b[30m` | Text Black |
+/// | `.RED` | `\x1b[31m` | Text Red |
+/// | `.GRN` | `\x1b[32m` | Text Green |
+/// | `.YLW` | `\x1b[33m` | Text Yellow |
+/// | `.BLU` | `\x1b[34m` | Text Blue |
+/// | `.MGT` | `\x1b[35m` | Text Magenta |
+/// | `.CYN` | `\x1b[36m` | Text Cyan |
+/// | `.WHT` | `\x1b[37m` | Text White |
+/// | `#BLK` | `\x1b[40m` | Background Black |
+/// | `#RED` | `\x1b[41m` | Background Red |
+/// | `#GRN` | `\x1b[42m` | Background Green |
+/// | `#YLW` | `\x1b[43m` | Background Yellow |
+/// | `#BLU` | `\x1b[44m` | Background Blue |
+/// | `#MGT` | `\x1b[45m` | Background Magenta |
+/// | `#CYN` | `\x1b[46m` | Background Cyan |
+/// | `#WHT` | `\x1b[47m` | Background White |
+/// | `.BBLK` | `\x1b[90m` | Text Bright Black |
+/// | `.BRED` | `\x1b[91m` | Text Bright Red |
+/// | `.BGRN` | `\x1b[92m` | Text Bright Green |
+/// | `.BYLW` | `\x1b[93m` | Text Bright Yellow |
+/// | `.BBLU` | `\x1b[94m` | Text Bright Blue |
+/// | `.BMGT` | `\x1b[95m` | Text Bright Magenta |
+/// | `.BCYN` | `\x1b[96m` | Text Bright Cyan |
+/// | `.BWHT` | `\x1b[97m` | Text Bright White |
+/// | `#BBLK` | `\x1b[100m` | Background Bright Black |
+/// | `#BRED` | `\x1b[101m` | Background Bright Red |
+/// | `#BGRN` | `\x1b[102m` | Background Bright Green |
+/// | `#BYLW` | `\x1b[103m` | Background Bright Yellow |
+/// | `#BBLU` | `\x1b[104m` | Background Bright Blue |
+/// | `#BMGT` | `\x1b[105m` | Background Bright Magenta |
+/// | `#BCYN` | `\x1b[106m` | Background Bright Cyan |
+/// | `#BWHT` | `\x1b[107m` | Background Bright White |
+/// | `.C0` to `.C255` | `\x1b[38;5;0m` to `\x1b[38;5;255m` | Text 256 Color Palette |
+/// | `#C0` to `#C255` | `\x1b[48;5;0m` to `\x1b[48;5;255m` | Background 256 Color Palette |
+/// | `.#ff8800`, `.(#ff8800)` | `\x1b[38;2;255;136;0m` | Text Hex Color |
+/// | `#(#ff8800)` | `\x1b[48;2;255;136;0m` | Background Hex Color |
+/// | `.orange`, `.(orange)` | `\x1b[38;2;255;165;0m` | Text CSS Color |
+/// | `#orange`, `#(orange)` | `\x1b[48;2;255;165;0m` | Background CSS Color |
+/// 
+#[proc_macro]
+pub fn colprint(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as colprint::ColArgs);
+
+    TokenStream::from(colprint::colprint_macro(args, quote!(print), quote!(Stdout)))
+}
+
+/// Macro which replaces the custom codes in its format string with ansi
+/// color escape codes at compile time and [`println!`]s it to the stdout.
+/// 
+/// Uses the same markup as [`colprint!`], replaced at compile time in the
+/// format string only, and follows the same colour policy.
+/// 
+/// ---
+/// # Examples
+/// ```
+/// use utils::colprintln;
+/// 
+/// colprintln!("#CYN.WHT_BLD_ITLHello, world!_X");
+/// 
+/// let file = "report.RED_X.txt";
+/// colprintln!(".GRNSaved_X {file}");
+/// ```
+#[proc_macro]
+pub fn colprintln(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as colprint::ColArgs);
+
+    TokenStream::from(colprint::colprint_macro(args, quote!(println), quote!(Stdout)))
+}
+
+/// Macro which replaces the custom codes in its format string with ansi
+/// color escape codes at compile time and returns the [`format!`]ted
+/// [`String`].
+/// 
+/// Uses the same markup as [`colprint!`], replaced at compile time in the
+/// format string only. As where the string ends up isn't known, the codes
+/// are always written, like `utils::ansi::replace_cc`, so check
+/// `utils::ansi::policy::enabled` for the stream it's written to first.
+/// Colours are still turned into the closest the terminal can show, and
+/// links are written like `utils::ansi::markup::render`.
+/// 
+/// ---
+/// # Examples
+/// ```
+/// use utils::{colformat, ansi::policy::{self, Stream}};
+/// 
+/// let name = "world";
+/// let output = colformat!("_BLD.GRNHello, {name}!_X");
+/// assert_eq!(output, "\x1b[1m\x1b[32mHello, world!\x1b[0m");
+/// 
+/// let status = match policy::enabled(Stream::Stderr) {
+///     true => colformat!(".REDfailed_X"),
+///     false => String::from("failed"),
+/// };
+/// eprintln!("{status}");
+/// ```
+#[proc_macro]
+pub fn colformat(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as colprint::ColArgs);
+
+    TokenStream::from(colprint::colformat_macro(args))
+}
+
+/// Macro which replaces the custom codes in its format string with ansi
+/// color escape codes at compile time and [`write!`]s it to the given
+/// destination, which can be any [`std::io::Write`] or [`std::fmt::Write`].
+/// 
+/// Uses the same markup as [`colprint!`], replaced at compile time in the
+/// format string only, and always writes the codes like [`colformat!`].
+/// 
+/// ---
+/// # Examples
+/// ```
+/// use std::fmt::Write;
+/// use utils::colwrite;
+/// 
+/// let mut output = String::new();
+/// colwrite!(output, ".RED{}_X", 404).unwrap();
+/// assert_eq!(output, "\x1b[31m404\x1b[0m");
+/// ```
+#[proc_macro]
+pub fn colwrite(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as colprint::ColWriteArgs);
+
+    TokenStream::from(colprint::colwrite_macro(args, quote!(write)))
+}
+
+/// Macro which replaces the custom codes in its format string with ansi
+/// color escape codes at compile time and [`writeln!`]s it to the given
+/// destination, which can be any [`std::io::Write`] or [`std::fmt::Write`].
+/// 
+/// Uses the same markup as [`colprint!`], replaced at compile time in the
+/// format string only, and always writes the codes like [`colformat!`].
+/// 
+/// ---
+/// # Examples
+/// ```
+/// use std::io::Write;
+/// use utils::colwriteln;
+/// 
+/// let mut output = Vec::new();
+/// colwriteln!(output, "#BLU.WHTInfo:_X {}", "done").unwrap();
+/// assert_eq!(output, b"\x1b[44m\x1b[37mInfo:\x1b[0m done\n");
+/// ```
+#[proc_macro]
+pub fn colwriteln(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as colprint::ColWriteArgs);
+
+    TokenStream::from(colprint::colwrite_macro(args, quote!(writeln)))
+}
+
+/// Macro which replaces the custom codes in its format string with ansi
+/// color escape codes at compile time and [`eprint!`]s it to the stderr.
+/// 
+/// Uses the same markup as [`colprint!`], replaced at compile time in the
+/// format string only, and follows the same colour policy.
+/// 
+/// ---
+/// # Examples
+/// ```
+/// use utils::ecolprint;
+/// 
+/// ecolprint!(".REDerror:_X {}", "file not found");
+/// ```
+#[proc_macro]
+pub fn ecolprint(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as colprint::ColArgs);
+
+    TokenStream::from(colprint::colprint_macro(args, quote!(eprint), quote!(Stderr)))
+}
+
+/// Macro which replaces the custom codes in its format string with ansi
+/// color escape codes at compile time and [`eprintln!`]s it to the stderr.
+/// 
+/// Uses the same markup as [`colprint!`], replaced at compile time in the
+/// format string only, and follows the same colour policy.
+/// 
+/// ---
+/// # Examples
+/// ```
+/// use utils::ecolprintln;
+/// 
+/// ecolprintln!(".REDerror:_X {}", "file not found");
+/// ```
+#[proc_macro]
+pub fn ecolprintln(input: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(input as colprint::ColArgs);
+
+    TokenStream::from(colprint::colprint_macro(args, quote!(eprintln), quote!(Stderr)))
+}
@@ -0,0 +1,79 @@
+pub const REPLACE_MAP: [(&str, &str); 53] = [
+    ("_X", X), ("_BLD", BLD), ("_ITL", ITL), ("_UND", UND),
+    ("_DIM", DIM), ("_BLN", BLN), ("_REV", REV), ("_HID", HID), ("_STK", STK), ("_DUN", DUN), ("_OVL", OVL),
+    ("_NBLD", NBLD), ("_NITL", NITL), ("_NUND", NUND), ("_NBLN", NBLN), ("_NREV", NREV), ("_NHID", NHID), ("_NSTK", NSTK), ("_NOVL", NOVL),
+    (".DEF", DEF), ("#DEF", BDEF),
+    (".BLK", BLK), (".RED", RED), (".GRN", GRN), (".YLW", YLW), (".BLU", BLU), (".MGT", MGT), (".CYN", CYN), (".WHT", WHT),
+    ("#BLK", BBLK), ("#RED", BRED), ("#GRN", BGRN), ("#YLW", BYLW), ("#BLU", BBLU), ("#MGT", BMGT), ("#CYN", BCYN), ("#WHT", BWHT),
+    (".BBLK", BRBLK), (".BRED", BRRED), (".BGRN", BRGRN), (".BYLW", BRYLW), (".BBLU", BRBLU), (".BMGT", BRMGT), (".BCYN", BRCYN), (".BWHT", BRWHT),
+    ("#BBLK", BBRBLK), ("#BRED", BBRRED), ("#BGRN", BBRGRN), ("#BYLW", BBRYLW), ("#BBLU", BBRBLU), ("#BMGT", BBRMGT), ("#BCYN", BBRCYN), ("#BWHT", BBRWHT)
+];
+
+// Denoted by a _
+pub const X:       &str = "\x1b[0m";  // Clear code
+
+// Foreground colours, `.RED` in markup
+pub const BLK: &str = "\x1b[30m"; // Black
+pub const RED: &str = "\x1b[31m"; // Red
+pub const GRN: &str = "\x1b[32m"; // Green
+pub const YLW: &str = "\x1b[33m"; // Yellow
+pub const BLU: &str = "\x1b[34m"; // Blue
+pub const MGT: &str = "\x1b[35m"; // Magenta
+pub const CYN: &str = "\x1b[36m"; // Cyan
+pub const WHT: &str = "\x1b[37m"; // White
+
+// Bright foreground colours, `.BRED` in markup
+pub const BRBLK: &str = "\x1b[90m"; // Bright Black
+pub const BRRED: &str = "\x1b[91m"; // Bright Red
+pub const BRGRN: &str = "\x1b[92m"; // Bright Green
+pub const BRYLW: &str = "\x1b[93m"; // Bright Yellow
+pub const BRBLU: &str = "\x1b[94m"; // Bright Blue
+pub const BRMGT: &str = "\x1b[95m"; // Bright Magenta
+pub const BRCYN: &str = "\x1b[96m"; // Bright Cyan
+pub const BRWHT: &str = "\x1b[97m"; // Bright White
+
+// Background colours, `#RED` in markup. The `B` of a constant means
+// background, while in markup it means bright
+pub const BBLK: &str = "\x1b[40m"; // Black
+pub const BRED: &str = "\x1b[41m"; // Red
+pub const BGRN: &str = "\x1b[42m"; // Green
+pub const BYLW: &str = "\x1b[43m"; // Yellow
+pub const BBLU: &str = "\x1b[44m"; // Blue
+pub const BMGT: &str = "\x1b[45m"; // Magenta
+pub const BCYN: &str = "\x1b[46m"; // Cyan
+pub const BWHT: &str = "\x1b[47m"; // White
+
+// Bright background colours, `#BRED` in markup
+pub const BBRBLK: &str = "\x1b[100m"; // Bright Black
+pub const BBRRED: &str = "\x1b[101m"; // Bright Red
+pub const BBRGRN: &str = "\x1b[102m"; // Bright Green
+pub const BBRYLW: &str = "\x1b[103m"; // Bright Yellow
+pub const BBRBLU: &str = "\x1b[104m"; // Bright Blue
+pub const BBRMGT: &str = "\x1b[105m"; // Bright Magenta
+pub const BBRCYN: &str = "\x1b[106m"; // Bright Cyan
+pub const BBRWHT: &str = "\x1b[107m"; // Bright White
+
+pub const BLD: &str = "\x1b[1m";  // Bold
+pub const ITL: &str = "\x1b[3m";  // Italicized
+pub const UND: &str = "\x1b[4m";  // Underline
+pub const DIM: &str = "\x1b[2m";  // Dim
+pub const BLN: &str = "\x1b[5m";  // Blink
+pub const REV: &str = "\x1b[7m";  // Reverse
+pub const HID: &str = "\x1b[8m";  // Hidden
+pub const STK: &str = "\x1b[9m";  // Strikethrough
+pub const DUN: &str = "\x1b[21m"; // Double underline
+pub const OVL: &str = "\x1b[53m"; // Overline
+
+// Targeted resets, clearing one style or colour and leaving the rest
+pub const NBLD: &str = "\x1b[22m"; // Not bold or dim
+pub const NITL: &str = "\x1b[23m"; // Not italicized
+pub const NUND: &str = "\x1b[24m"; // Not underlined or double underlined
+pub const NBLN: &str = "\x1b[25m"; // Not blinking
+pub const NREV: &str = "\x1b[27m"; // Not reversed
+pub const NHID: &str = "\x1b[28m"; // Not hidden
+pub const NSTK: &str = "\x1b[29m"; // Not strikethrough
+pub const NOVL: &str = "\x1b[55m"; // Not overlined
+pub const DEF:  &str = "\x1b[39m"; // Default foreground
+pub const BDEF: &str = "\x1b[49m"; // Default background
+// pub mod style { // Denoted by a _
+// }
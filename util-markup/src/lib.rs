@@ -0,0 +1,14 @@
+//! The escape codes, colour palette and markup tokenizer shared by `utils`
+//! and the macros of `util-derive`, which replace the same markup at
+//! compile time. Use them through `utils::ansi` rather than this crate.
+
+pub mod codes;
+
+/// Conversions between the 256 colour palette and RGB colours.
+pub mod palette;
+
+/// Tags of the scoped markup, `[bold red]...[/]`.
+pub mod tags;
+
+/// The `_`/`.`/`#` codes of the markup.
+pub mod tokens;
@@ -0,0 +1,352 @@
+/// The default xterm colours of the 16 standard and bright colours, the
+/// first 16 entries of the 256 colour palette. Most terminals let users
+/// change these, so they are only a best guess.
+pub const ANSI16: [[u8; 3]; 16] = [
+    [0, 0, 0], [205, 0, 0], [0, 205, 0], [205, 205, 0],
+    [0, 0, 238], [205, 0, 205], [0, 205, 205], [229, 229, 229],
+    [127, 127, 127], [255, 0, 0], [0, 255, 0], [255, 255, 0],
+    [92, 92, 255], [255, 0, 255], [0, 255, 255], [255, 255, 255],
+];
+
+/// The 147 named colours of CSS, sorted by name, as used by markup like
+/// `.orange` and `#slategray`.
+pub const CSS_COLORS: [(&str, [u8; 3]); 147] = [
+    ("aliceblue", [240, 248, 255]),
+    ("antiquewhite", [250, 235, 215]),
+    ("aqua", [0, 255, 255]),
+    ("aquamarine", [127, 255, 212]),
+    ("azure", [240, 255, 255]),
+    ("beige", [245, 245, 220]),
+    ("bisque", [255, 228, 196]),
+    ("black", [0, 0, 0]),
+    ("blanchedalmond", [255, 235, 205]),
+    ("blue", [0, 0, 255]),
+    ("blueviolet", [138, 43, 226]),
+    ("brown", [165, 42, 42]),
+    ("burlywood", [222, 184, 135]),
+    ("cadetblue", [95, 158, 160]),
+    ("chartreuse", [127, 255, 0]),
+    ("chocolate", [210, 105, 30]),
+    ("coral", [255, 127, 80]),
+    ("cornflowerblue", [100, 149, 237]),
+    ("cornsilk", [255, 248, 220]),
+    ("crimson", [220, 20, 60]),
+    ("cyan", [0, 255, 255]),
+    ("darkblue", [0, 0, 139]),
+    ("darkcyan", [0, 139, 139]),
+    ("darkgoldenrod", [184, 134, 11]),
+    ("darkgray", [169, 169, 169]),
+    ("darkgreen", [0, 100, 0]),
+    ("darkgrey", [169, 169, 169]),
+    ("darkkhaki", [189, 183, 107]),
+    ("darkmagenta", [139, 0, 139]),
+    ("darkolivegreen", [85, 107, 47]),
+    ("darkorange", [255, 140, 0]),
+    ("darkorchid", [153, 50, 204]),
+    ("darkred", [139, 0, 0]),
+    ("darksalmon", [233, 150, 122]),
+    ("darkseagreen", [143, 188, 143]),
+    ("darkslateblue", [72, 61, 139]),
+    ("darkslategray", [47, 79, 79]),
+    ("darkslategrey", [47, 79, 79]),
+    ("darkturquoise", [0, 206, 209]),
+    ("darkviolet", [148, 0, 211]),
+    ("deeppink", [255, 20, 147]),
+    ("deepskyblue", [0, 191, 255]),
+    ("dimgray", [105, 105, 105]),
+    ("dimgrey", [105, 105, 105]),
+    ("dodgerblue", [30, 144, 255]),
+    ("firebrick", [178, 34, 34]),
+    ("floralwhite", [255, 250, 240]),
+    ("forestgreen", [34, 139, 34]),
+    ("fuchsia", [255, 0, 255]),
+    ("gainsboro", [220, 220, 220]),
+    ("ghostwhite", [248, 248, 255]),
+    ("gold", [255, 215, 0]),
+    ("goldenrod", [218, 165, 32]),
+    ("gray", [128, 128, 128]),
+    ("green", [0, 128, 0]),
+    ("greenyellow", [173, 255, 47]),
+    ("grey", [128, 128, 128]),
+    ("honeydew", [240, 255, 240]),
+    ("hotpink", [255, 105, 180]),
+    ("indianred", [205, 92, 92]),
+    ("indigo", [75, 0, 130]),
+    ("ivory", [255, 255, 240]),
+    ("khaki", [240, 230, 140]),
+    ("lavender", [230, 230, 250]),
+    ("lavenderblush", [255, 240, 245]),
+    ("lawngreen", [124, 252, 0]),
+    ("lemonchiffon", [255, 250, 205]),
+    ("lightblue", [173, 216, 230]),
+    ("lightcoral", [240, 128, 128]),
+    ("lightcyan", [224, 255, 255]),
+    ("lightgoldenrodyellow", [250, 250, 210]),
+    ("lightgray", [211, 211, 211]),
+    ("lightgreen", [144, 238, 144]),
+    ("lightgrey", [211, 211, 211]),
+    ("lightpink", [255, 182, 193]),
+    ("lightsalmon", [255, 160, 122]),
+    ("lightseagreen", [32, 178, 170]),
+    ("lightskyblue", [135, 206, 250]),
+    ("lightslategray", [119, 136, 153]),
+    ("lightslategrey", [119, 136, 153]),
+    ("lightsteelblue", [176, 196, 222]),
+    ("lightyellow", [255, 255, 224]),
+    ("lime", [0, 255, 0]),
+    ("limegreen", [50, 205, 50]),
+    ("linen", [250, 240, 230]),
+    ("magenta", [255, 0, 255]),
+    ("maroon", [128, 0, 0]),
+    ("mediumaquamarine", [102, 205, 170]),
+    ("mediumblue", [0, 0, 205]),
+    ("mediumorchid", [186, 85, 211]),
+    ("mediumpurple", [147, 112, 219]),
+    ("mediumseagreen", [60, 179, 113]),
+    ("mediumslateblue", [123, 104, 238]),
+    ("mediumspringgreen", [0, 250, 154]),
+    ("mediumturquoise", [72, 209, 204]),
+    ("mediumvioletred", [199, 21, 133]),
+    ("midnightblue", [25, 25, 112]),
+    ("mintcream", [245, 255, 250]),
+    ("mistyrose", [255, 228, 225]),
+    ("moccasin", [255, 228, 181]),
+    ("navajowhite", [255, 222, 173]),
+    ("navy", [0, 0, 128]),
+    ("oldlace", [253, 245, 230]),
+    ("olive", [128, 128, 0]),
+    ("olivedrab", [107, 142, 35]),
+    ("orange", [255, 165, 0]),
+    ("orangered", [255, 69, 0]),
+    ("orchid", [218, 112, 214]),
+    ("palegoldenrod", [238, 232, 170]),
+    ("palegreen", [152, 251, 152]),
+    ("paleturquoise", [175, 238, 238]),
+    ("palevioletred", [219, 112, 147]),
+    ("papayawhip", [255, 239, 213]),
+    ("peachpuff", [255, 218, 185]),
+    ("peru", [205, 133, 63]),
+    ("pink", [255, 192, 203]),
+    ("plum", [221, 160, 221]),
+    ("powderblue", [176, 224, 230]),
+    ("purple", [128, 0, 128]),
+    ("red", [255, 0, 0]),
+    ("rosybrown", [188, 143, 143]),
+    ("royalblue", [65, 105, 225]),
+    ("saddlebrown", [139, 69, 19]),
+    ("salmon", [250, 128, 114]),
+    ("sandybrown", [244, 164, 96]),
+    ("seagreen", [46, 139, 87]),
+    ("seashell", [255, 245, 238]),
+    ("sienna", [160, 82, 45]),
+    ("silver", [192, 192, 192]),
+    ("skyblue", [135, 206, 235]),
+    ("slateblue", [106, 90, 205]),
+    ("slategray", [112, 128, 144]),
+    ("slategrey", [112, 128, 144]),
+    ("snow", [255, 250, 250]),
+    ("springgreen", [0, 255, 127]),
+    ("steelblue", [70, 130, 180]),
+    ("tan", [210, 180, 140]),
+    ("teal", [0, 128, 128]),
+    ("thistle", [216, 191, 216]),
+    ("tomato", [255, 99, 71]),
+    ("turquoise", [64, 224, 208]),
+    ("violet", [238, 130, 238]),
+    ("wheat", [245, 222, 179]),
+    ("white", [255, 255, 255]),
+    ("whitesmoke", [245, 245, 245]),
+    ("yellow", [255, 255, 0]),
+    ("yellowgreen", [154, 205, 50]),
+];
+
+/// The RGB colour of a CSS colour name, see [`CSS_COLORS`].
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::palette::css_color;
+/// 
+/// assert_eq!(css_color("orange"), Some([255, 165, 0]));
+/// assert_eq!(css_color("Orange"), None);
+/// ```
+pub fn css_color(name: &str) -> Option<[u8; 3]> {
+    CSS_COLORS
+        .binary_search_by_key(&name, |(name, _)| name)
+        .ok()
+        .map(|i| CSS_COLORS[i].1)
+}
+
+/// Parses a colour written as `#rrggbb`, `#rgb` or a CSS colour name.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::palette::parse_color;
+/// 
+/// assert_eq!(parse_color("#ff8800"), Some([255, 136, 0]));
+/// assert_eq!(parse_color("#f80"), Some([255, 136, 0]));
+/// assert_eq!(parse_color("slategray"), Some([112, 128, 144]));
+/// assert_eq!(parse_color("#ff88"), None);
+/// ```
+pub fn parse_color(s: &str) -> Option<[u8; 3]> {
+    let Some(hex) = s.strip_prefix('#') else {
+        return css_color(s);
+    };
+
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize, len: usize| u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).ok();
+    match hex.len() {
+        6 => Some([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?]),
+        3 => Some([channel(0, 1)? * 17, channel(1, 1)? * 17, channel(2, 1)? * 17]),
+        _ => None,
+    }
+}
+
+/// The levels of each channel in the 6x6x6 colour cube, indices 16 to 231.
+pub const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// Converts an index in the 256 colour palette to its RGB colour.
+/// 
+/// Indices 0 to 15 are the standard and bright colours, 16 to 231 are a
+/// 6x6x6 colour cube and 232 to 255 are a ramp of greys.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::palette::ansi256_to_rgb;
+/// 
+/// assert_eq!(ansi256_to_rgb(208), [255, 135, 0]);
+/// assert_eq!(ansi256_to_rgb(244), [128, 128, 128]);
+/// ```
+pub fn ansi256_to_rgb(index: u8) -> [u8; 3] {
+    match index {
+        0..=15 => ANSI16[index as usize],
+        16..=231 => {
+            let i = index - 16;
+            [i / 36, i / 6 % 6, i % 6].map(|c| CUBE_LEVELS[c as usize])
+        },
+        232..=255 => [8 + (index - 232) * 10; 3],
+    }
+}
+
+/// How many colours a terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorDepth {
+    /// The 8 standard and 8 bright colours.
+    Ansi16,
+    /// The 256 colour palette.
+    Ansi256,
+    /// 24-bit RGB colours.
+    TrueColor,
+}
+
+/// Converts an sRGB colour to the OKLab colour space, in which the distance
+/// between two colours is close to how different they look.
+fn oklab(rgb: [u8; 3]) -> [f32; 3] {
+    let [r, g, b] = rgb.map(|c| {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+    });
+
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+    [
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    ]
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum()
+}
+
+/// The 256 colour palette in the OKLab colour space.
+fn palette_oklab() -> &'static [[f32; 3]; 256] {
+    static PALETTE: std::sync::OnceLock<[[f32; 3]; 256]> = std::sync::OnceLock::new();
+
+    PALETTE.get_or_init(|| std::array::from_fn(|i| oklab(ansi256_to_rgb(i as u8))))
+}
+
+/// Index of the colour in `range` of the 256 colour palette which looks
+/// the closest to `rgb`.
+fn closest(rgb: [u8; 3], range: std::ops::RangeInclusive<u8>) -> u8 {
+    let lab = oklab(rgb);
+    let palette = palette_oklab();
+
+    range
+        .min_by(|&a, &b| {
+            distance(lab, palette[a as usize]).total_cmp(&distance(lab, palette[b as usize]))
+        })
+        .unwrap()
+}
+
+/// Converts an RGB colour to the index of the closest looking colour in
+/// the 256 colour palette, out of the colour cube and the grey ramp. The
+/// first 16 colours are skipped as they depend on the terminal.
+/// 
+/// Closeness is measured in the OKLab colour space, rather than by the
+/// distance between the RGB values, to match how colours are seen.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::palette::rgb_to_ansi256;
+/// 
+/// assert_eq!(rgb_to_ansi256([255, 135, 0]), 208);
+/// assert_eq!(rgb_to_ansi256([250, 130, 10]), 208);
+/// assert_eq!(rgb_to_ansi256([128, 128, 128]), 244);
+/// ```
+pub fn rgb_to_ansi256(rgb: [u8; 3]) -> u8 {
+    closest(rgb, 16..=255)
+}
+
+/// Converts an RGB colour to the index of the closest looking of the 16
+/// standard and bright colours, as measured in the OKLab colour space.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::palette::rgb_to_ansi16;
+/// 
+/// assert_eq!(rgb_to_ansi16([200, 10, 10]), 1);
+/// assert_eq!(rgb_to_ansi16([250, 250, 250]), 15);
+/// ```
+pub fn rgb_to_ansi16(rgb: [u8; 3]) -> u8 {
+    closest(rgb, 0..=15)
+}
+
+/// Converts an index in the 256 colour palette to the index of the
+/// closest looking of the 16 standard and bright colours.
+/// 
+/// ---
+/// ## Examples
+/// ```
+/// use utils::ansi::palette::ansi256_to_ansi16;
+/// 
+/// assert_eq!(ansi256_to_ansi16(9), 9);
+/// assert_eq!(ansi256_to_ansi16(196), 9);
+/// ```
+pub fn ansi256_to_ansi16(index: u8) -> u8 {
+    match index {
+        0..=15 => index,
+        _ => rgb_to_ansi16(ansi256_to_rgb(index)),
+    }
+}
+
+/// The SGR parameter setting one of the 16 standard and bright colours,
+/// as the foreground or `background`.
+pub fn ansi16_sgr(index: u8, background: bool) -> u8 {
+    let base = if background { 40 } else { 30 };
+
+    match index {
+        0..=7 => base + index,
+        _ => base + 60 + (index & 7),
+    }
+}
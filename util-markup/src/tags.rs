@@ -0,0 +1,314 @@
+use std::ops::Range;
+
+use crate::codes::*;
+use crate::palette::parse_color;
+use crate::tokens::{Token, Tokens};
+
+/// The words for styles in a tag, with the code turning each on.
+pub const STYLES: [(&str, &str); 10] = [
+    ("bold", BLD),
+    ("dim", DIM),
+    ("italic", ITL),
+    ("underline", UND),
+    ("blink", BLN),
+    ("reverse", REV),
+    ("hidden", HID),
+    ("strikethrough", STK),
+    ("double_underline", DUN),
+    ("overline", OVL),
+];
+
+/// The words for the terminal's own colours in a tag, with their foreground
+/// and background codes.
+pub const COLORS: [(&str, &str, &str); 17] = [
+    ("default", DEF, BDEF),
+    ("black", BLK, BBLK),
+    ("red", RED, BRED),
+    ("green", GRN, BGRN),
+    ("yellow", YLW, BYLW),
+    ("blue", BLU, BBLU),
+    ("magenta", MGT, BMGT),
+    ("cyan", CYN, BCYN),
+    ("white", WHT, BWHT),
+    ("bright_black", BRBLK, BBRBLK),
+    ("bright_red", BRRED, BBRRED),
+    ("bright_green", BRGRN, BBRGRN),
+    ("bright_yellow", BRYLW, BBRYLW),
+    ("bright_blue", BRBLU, BBRBLU),
+    ("bright_magenta", BRMGT, BBRMGT),
+    ("bright_cyan", BRCYN, BBRCYN),
+    ("bright_white", BRWHT, BBRWHT),
+];
+
+/// Parses a colour word of a tag, as the foreground or `background`.
+pub fn parse_tag_color(word: &str, background: bool) -> Option<Token<'static>> {
+    if let Some((_, fg, bg)) = COLORS.iter().find(|(name, ..)| *name == word) {
+        return Some(Token::Code(if background { bg } else { fg }));
+    }
+
+    if let Some(index) = word.strip_prefix("color(").and_then(|w| w.strip_suffix(')')) {
+        return Some(Token::Ansi256 { background, index: index.parse().ok()? });
+    }
+
+    parse_color(word).map(|rgb| Token::Rgb { background, rgb })
+}
+
+/// Parses the inside of an opening tag, such as `bold red on #202020` or
+/// `link=https://example.com`, into the tokens turning its style on.
+pub fn parse_tag(spec: &str) -> Option<Vec<Token<'_>>> {
+    if let Some(url) = spec.strip_prefix("link=") {
+        let valid = !url.is_empty() && !url.contains(|c: char| c.is_whitespace() || c.is_control());
+        return valid.then(|| vec![Token::Link(url)]);
+    }
+
+    let mut tokens = Vec::new();
+    let mut words = spec.split_whitespace();
+
+    while let Some(word) = words.next() {
+        let token = match word {
+            "on" => parse_tag_color(words.next()?, true)?,
+            word => STYLES
+                .iter()
+                .find(|(name, _)| *name == word)
+                .map(|(_, code)| Token::Code(code))
+                .or_else(|| parse_tag_color(word, false))?,
+        };
+        tokens.push(token);
+    }
+
+    (!tokens.is_empty()).then_some(tokens)
+}
+
+/// A style opened by a tag, along with any codes written inside of it.
+struct Frame<'a> {
+    tag: &'a str,
+    tokens: Vec<Token<'a>>,
+}
+
+impl<'a> Frame<'a> {
+    fn link(&self) -> Option<&'a str> {
+        self.tag.strip_prefix("link=")
+    }
+
+    /// Whether the frame sets any style, which a reset would turn off.
+    fn has_style(&self) -> bool {
+        self.tokens.iter().any(|token| !matches!(token, Token::Link(_)))
+    }
+}
+
+enum Tag<'a> {
+    Open(&'a str, Vec<Token<'a>>),
+    /// Closes the frame at this index and all frames above it.
+    Close(usize),
+}
+
+/// Stack based renderer of the `[...]`/`[/...]` tag markup, on top of the
+/// `_`/`.`/`#` codes of [`Tokens`].
+/// 
+/// Text can be fed in pieces, with the tags open at the end of one piece
+/// carrying on into the next, which is how the `colprint!` macros skip
+/// over the placeholders of their format strings.
+pub struct Markup<'a> {
+    /// The style of the text outside of any tags is at the bottom.
+    frames: Vec<Frame<'a>>,
+    unmatched: Option<Range<usize>>,
+}
+
+impl<'a> Markup<'a> {
+    pub fn new() -> Self {
+        Self { frames: vec![Frame { tag: "", tokens: Vec::new() }], unmatched: None }
+    }
+
+    /// Matches a tag at the start of `s`, returning its length. A closing
+    /// tag which doesn't match any open tag is matched as [`None`].
+    fn match_tag(&self, s: &'a str) -> Option<(usize, Option<Tag<'a>>)> {
+        let end = s.find(']')?;
+        let spec = &s[1..end];
+        if spec.contains('[') {
+            return None;
+        }
+
+        let Some(spec) = spec.strip_prefix('/') else {
+            return parse_tag(spec).map(|tokens| (end + 1, Some(Tag::Open(spec, tokens))));
+        };
+
+        let index = self
+            .frames
+            .iter()
+            .rposition(|frame| {
+                spec.is_empty()
+                    || spec == "link" && frame.link().is_some()
+                    || frame.tag.split_whitespace().eq(spec.split_whitespace())
+            })
+            .filter(|&index| index > 0);
+
+        Some((end + 1, index.map(Tag::Close)))
+    }
+
+    /// Splits `s` into tokens, calling `f` with each. Closing a tag goes
+    /// back to the style around it by resetting and turning that style
+    /// back on. Links aren't affected by resets, so they are only ended
+    /// when their own tag closes.
+    pub fn feed(&mut self, s: &'a str, mut f: impl FnMut(Token<'a>)) {
+        // Start of the text which hasn't been written yet.
+        let mut pos = 0;
+        let mut search = 0;
+
+        while let Some(i) = s[search..].find('[').map(|i| search + i) {
+            search = i + 1;
+            let Some((len, tag)) = self.match_tag(&s[i..]) else {
+                continue;
+            };
+
+            let before = &s[pos..i];
+            if before.ends_with('\\') && !before.ends_with("\\\\") {
+                // `\[red]` is the literal text `[red]`.
+                self.text(&before[..before.len() - 1], &mut f);
+                pos = i;
+                continue;
+            }
+            let Some(tag) = tag else {
+                self.unmatched.get_or_insert(i..i + len);
+                continue;
+            };
+
+            // `\\[red]` is a literal backslash followed by a live tag.
+            let before = before.strip_suffix('\\').unwrap_or(before);
+            self.text(before, &mut f);
+            pos = i + len;
+            search = pos;
+
+            match tag {
+                Tag::Open(spec, tokens) => {
+                    tokens.iter().for_each(|&token| f(token));
+                    self.frames.push(Frame { tag: spec, tokens });
+                },
+                Tag::Close(index) => {
+                    let closed = self.frames.split_off(index);
+                    closed.iter().rev().filter_map(Frame::link).for_each(|url| f(Token::LinkEnd(url)));
+
+                    if closed.iter().any(Frame::has_style) {
+                        f(Token::Code(X));
+                        self.frames
+                            .iter()
+                            .flat_map(|frame| &frame.tokens)
+                            .filter(|token| !matches!(token, Token::Link(_)))
+                            .for_each(|&token| f(token));
+                    }
+                },
+            }
+        }
+
+        self.text(&s[pos..], &mut f);
+    }
+
+    /// Writes a piece of text between tags, keeping track of its codes so
+    /// they can be turned back on when a tag inside of them closes.
+    fn text(&mut self, s: &'a str, f: &mut impl FnMut(Token<'a>)) {
+        for token in Tokens::new(s) {
+            match token {
+                Token::Text(_) => (),
+                Token::Code(X) => self.frames.iter_mut().for_each(|frame| {
+                    frame.tokens.retain(|token| matches!(token, Token::Link(_)));
+                }),
+                token => self.frames.last_mut().unwrap().tokens.push(token),
+            }
+            f(token);
+        }
+    }
+
+    /// The range of the first closing tag fed since the last call which
+    /// didn't match any open tag, and was written as text. Used by the
+    /// `colprint!` macros to make these an error.
+    pub fn take_unmatched(&mut self) -> Option<Range<usize>> {
+        self.unmatched.take()
+    }
+
+    /// Ends the markup, ending any links and resetting the style if any
+    /// tags are left open.
+    pub fn finish(self, mut f: impl FnMut(Token<'a>)) {
+        let open = &self.frames[1..];
+        open.iter().rev().filter_map(Frame::link).for_each(|url| f(Token::LinkEnd(url)));
+
+        if open.iter().any(Frame::has_style) {
+            f(Token::Code(X));
+        }
+    }
+}
+
+impl Default for Markup<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renders `pieces` as one markup, as the `colprint!` macros do.
+    fn render(pieces: &[&'static str]) -> String {
+        let mut out = String::new();
+        let mut markup = Markup::new();
+        for piece in pieces {
+            markup.feed(piece, |token| out.push_str(&token.to_string()));
+        }
+        markup.finish(|token| out.push_str(&token.to_string()));
+        out
+    }
+
+    #[test]
+    fn tags() {
+        assert_eq!(parse_tag("bold red on #202020"), Some(vec![
+            Token::Code(BLD),
+            Token::Code(RED),
+            Token::Rgb { background: true, rgb: [32, 32, 32] },
+        ]));
+        assert_eq!(parse_tag("color(208) on bright_blue"), Some(vec![
+            Token::Ansi256 { background: false, index: 208 },
+            Token::Code(BBRBLU),
+        ]));
+        assert_eq!(parse_tag("link=https://example.com"), Some(vec![Token::Link("https://example.com")]));
+        for spec in ["", "bold on", "on bold", "color(256)", "reds", "link=", "link=a b", "link=a\x1bb"] {
+            assert_eq!(parse_tag(spec), None, "{spec:?}");
+        }
+    }
+
+    #[test]
+    fn closing_restores_the_outer_style() {
+        assert_eq!(render(&["[bold]a[red]b[/red]c[/bold]d"]), format!("{BLD}a{RED}b{X}{BLD}c{X}d"));
+        assert_eq!(render(&[".GRN[bold]a[/]b"]), format!("{GRN}{BLD}a{X}{GRN}b"));
+        assert_eq!(render(&["[bold]a[italic]b[/bold]c"]), format!("{BLD}a{ITL}b{X}c"));
+        assert_eq!(render(&["[bold]a_Xb[/bold]c"]), format!("{BLD}a{X}bc"));
+    }
+
+    #[test]
+    fn tags_carry_across_pieces() {
+        assert_eq!(render(&["[red]a", "b[/red]", "c[bold]"]), format!("{RED}ab{X}c{BLD}{X}"));
+    }
+
+    #[test]
+    fn links_outlive_resets() {
+        let link = "\x1b]8;;https://x.y\x1b\\";
+        let end = "\x1b]8;;\x1b\\";
+        assert_eq!(render(&["[link=https://x.y]a[bold]b[/bold]c[/link]d"]), format!("{link}a{BLD}b{X}c{end}d"));
+        assert_eq!(render(&["[link=https://x.y][red]a_Xb[/link]"]), format!("{link}{RED}a{X}b{end}"));
+        assert_eq!(render(&["[link=https://x.y]a"]), format!("{link}a{end}"));
+    }
+
+    #[test]
+    fn escaped_and_unmatched_tags() {
+        assert_eq!(render(&["\\[red]a"]), "[red]a");
+        assert_eq!(render(&["\\\\[red]a"]), format!("\\{RED}a{X}"));
+        assert_eq!(render(&["[not a tag]a[1]"]), "[not a tag]a[1]");
+
+        // Brackets holding only tag words are always a tag.
+        assert_eq!(render(&["[default] options [/]"]), format!("{DEF} options {X}"));
+        assert_eq!(render(&["\\[default] options \\[/] [bold]"]), format!("[default] options [/] {BLD}{X}"));
+
+        let mut markup = Markup::new();
+        markup.feed("[red]a[/blue]b[/red]c[/red]", |_| ());
+        assert_eq!(markup.take_unmatched(), Some(6..13));
+        assert_eq!(markup.take_unmatched(), None);
+    }
+}
@@ -0,0 +1,312 @@
+use std::fmt;
+
+use crate::palette::{
+    ansi16_sgr, ansi256_to_ansi16, css_color, parse_color, rgb_to_ansi16, rgb_to_ansi256,
+    ColorDepth,
+};
+use crate::codes::REPLACE_MAP;
+
+/// A single piece of a markup string, either text to be copied through
+/// as is or a code to be swapped for its ansi escape sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    Text(&'a str),
+    Code(&'static str),
+    /// A colour from the 256 colour palette, `.C208` or `#C208`.
+    Ansi256 { background: bool, index: u8 },
+    /// A hex or CSS named colour, `.#ff8800`, `#(#202020)` or `.orange`.
+    Rgb { background: bool, rgb: [u8; 3] },
+    /// The start of a hyperlink to the url, from a `[link=...]` tag.
+    Link(&'a str),
+    /// The end of a hyperlink to the url.
+    LinkEnd(&'a str),
+}
+
+impl fmt::Display for Token<'_> {
+    /// Writes the text, or the escape code, of the token.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Text(text) => f.write_str(text),
+            Token::Code(code) => f.write_str(code),
+            Token::Ansi256 { background: false, index } => write!(f, "\x1b[38;5;{index}m"),
+            Token::Ansi256 { background: true, index } => write!(f, "\x1b[48;5;{index}m"),
+            Token::Rgb { background: false, rgb: [r, g, b] } => write!(f, "\x1b[38;2;{r};{g};{b}m"),
+            Token::Rgb { background: true, rgb: [r, g, b] } => write!(f, "\x1b[48;2;{r};{g};{b}m"),
+            Token::Link(url) => {
+                f.write_str("\x1b]8;;")?;
+                write_url(url, f)?;
+                f.write_str("\x1b\\")
+            },
+            Token::LinkEnd(_) => f.write_str("\x1b]8;;\x1b\\"),
+        }
+    }
+}
+
+impl Token<'_> {
+    /// Writes the token for a terminal which can show `depth` colours.
+    pub fn write_at(&self, depth: ColorDepth, w: &mut impl fmt::Write) -> fmt::Result {
+        match *self {
+            Token::Ansi256 { background, index } if depth == ColorDepth::Ansi16 => {
+                write!(w, "\x1b[{}m", ansi16_sgr(ansi256_to_ansi16(index), background))
+            },
+            Token::Rgb { background, rgb } if depth == ColorDepth::Ansi16 => {
+                write!(w, "\x1b[{}m", ansi16_sgr(rgb_to_ansi16(rgb), background))
+            },
+            Token::Rgb { background, rgb } if depth == ColorDepth::Ansi256 => {
+                Token::Ansi256 { background, index: rgb_to_ansi256(rgb) }.write_at(depth, w)
+            },
+            token => write!(w, "{token}"),
+        }
+    }
+}
+
+/// Writes `url` with its control characters percent encoded, as they would
+/// end a hyperlink early or start another escape sequence.
+pub fn write_url(url: &str, w: &mut impl fmt::Write) -> fmt::Result {
+    for c in url.chars() {
+        if !c.is_control() {
+            w.write_char(c)?;
+            continue;
+        }
+
+        for b in c.encode_utf8(&mut [0; 4]).bytes() {
+            write!(w, "%{b:02X}")?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Single pass tokenizer over the `_`/`.`/`#` markup used by
+/// `utils::ansi::replace_cc`.
+pub struct Tokens<'a> {
+    s: &'a str,
+    pos: usize,
+    /// End of the last code, which the next one can directly follow.
+    code_end: usize,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(s: &'a str) -> Self {
+        Self { s, pos: 0, code_end: 0 }
+    }
+
+    /// Finds the code at byte `i` of the string, like [`match_code`]. A
+    /// palette colour or an unbracketed CSS name has to start a word or
+    /// directly follow another code, so `file.C12` and `report.red.txt` are
+    /// left as text but `.C208#C236` is two colours.
+    fn code_at(&self, i: usize) -> Option<(usize, Token<'static>)> {
+        let (len, token) = match_code(&self.s[i..])?;
+        let in_word = i != self.code_end && self.s[..i].ends_with(|c: char| c.is_alphanumeric() || c == '_');
+        let bracketed = self.s[i + 1..].starts_with(['(', '#']);
+
+        match token {
+            Token::Ansi256 { .. } if in_word => None,
+            Token::Rgb { .. } if in_word && !bracketed => None,
+            token => Some((len, token)),
+        }
+    }
+}
+
+/// Finds the longest code at the start of `s`, returning the length of the
+/// markup matched and the token it maps to.
+pub fn match_code(s: &str) -> Option<(usize, Token<'static>)> {
+    REPLACE_MAP
+        .iter()
+        .filter(|(from, _)| s.starts_with(from))
+        .max_by_key(|(from, _)| from.len())
+        .map(|(from, to)| (from.len(), Token::Code(to)))
+        .or_else(|| match_ansi256(s))
+        .or_else(|| match_rgb(s))
+}
+
+/// Matches a 256 colour palette code, `.C` or `#C` followed by up to three
+/// digits making up a number no bigger than 255.
+fn match_ansi256(s: &str) -> Option<(usize, Token<'static>)> {
+    let background = match s.get(..2)? {
+        ".C" => false,
+        "#C" => true,
+        _ => return None,
+    };
+
+    let digits = s[2..].bytes().take(3).take_while(u8::is_ascii_digit).count();
+    let index = s[2..2 + digits].parse().ok()?;
+
+    Some((2 + digits, Token::Ansi256 { background, index }))
+}
+
+/// Matches a hex or CSS named colour. Either form can be put in brackets,
+/// `.(#ff8800)` or `#(slategray)`, which is the only way to write a hex
+/// background. Unbracketed, a foreground hex colour is `.#` followed by
+/// exactly six hex digits and a CSS name has to be a whole word, so
+/// `.orangey` and `file.tan2` are left as text. [`Tokens`] also needs it to
+/// start a word.
+fn match_rgb(s: &str) -> Option<(usize, Token<'static>)> {
+    let background = match s.as_bytes().first()? {
+        b'.' => false,
+        b'#' => true,
+        _ => return None,
+    };
+    let rest = &s[1..];
+
+    if let Some(inner) = rest.strip_prefix('(') {
+        let end = inner.find(')')?;
+        let rgb = parse_color(&inner[..end])?;
+
+        return Some((end + 3, Token::Rgb { background, rgb }));
+    }
+
+    if !background && rest.starts_with('#') {
+        let rgb = parse_color(s.get(1..8)?)?;
+
+        return Some((8, Token::Rgb { background, rgb }));
+    }
+
+    let len = rest.bytes().take_while(u8::is_ascii_lowercase).count();
+    if rest[len..].starts_with(|c: char| c.is_alphanumeric()) {
+        return None;
+    }
+    let rgb = css_color(&rest[..len])?;
+
+    Some((len + 1, Token::Rgb { background, rgb }))
+}
+
+enum Escape {
+    /// A doubled backslash directly before a code, which still applies.
+    Backslash,
+    /// A backslash directly before a code of `len` bytes.
+    Code(usize),
+}
+
+/// Checks if `s` starts with an escape, either `\` followed by a code or
+/// `\\` followed by a code.
+fn match_escape(s: &str) -> Option<Escape> {
+    let rest = s.strip_prefix('\\')?;
+
+    if let Some((len, _)) = match_code(rest) {
+        return Some(Escape::Code(len));
+    }
+
+    rest.strip_prefix('\\')
+        .and_then(match_code)
+        .map(|_| Escape::Backslash)
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.s[self.pos..];
+
+        if rest.is_empty() {
+            return None;
+        }
+
+        if let Some((len, token)) = self.code_at(self.pos) {
+            self.pos += len;
+            self.code_end = self.pos;
+            return Some(token);
+        }
+
+        match match_escape(rest) {
+            // `\\_X` is a literal backslash followed by a live code.
+            Some(Escape::Backslash) => {
+                self.pos += 2;
+                return Some(Token::Text(&rest[..1]));
+            },
+            // `\_X` is the literal text `_X`.
+            Some(Escape::Code(len)) => {
+                self.pos += 1 + len;
+                return Some(Token::Text(&rest[1..1 + len]));
+            },
+            None => (),
+        }
+
+        // Copy through everything up to the next code or escape.
+        let mut end = rest.len();
+        for (i, c) in rest.match_indices(['_', '.', '#', '\\']).filter(|(i, _)| *i > 0) {
+            let starts_token = match c {
+                "\\" => match_escape(&rest[i..]).is_some(),
+                _ => self.code_at(self.pos + i).is_some(),
+            };
+
+            if starts_token {
+                end = i;
+                break;
+            }
+        }
+
+        self.pos += end;
+        Some(Token::Text(&rest[..end]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(s: &str) -> Vec<Token<'_>> {
+        Tokens::new(s).collect()
+    }
+
+    #[test]
+    fn palette_colours_start_a_word() {
+        assert_eq!(tokens("file.C12"), [Token::Text("file.C12")]);
+        assert_eq!(tokens("a_#C5"), [Token::Text("a_#C5")]);
+        assert_eq!(tokens("see .C12"), [
+            Token::Text("see "),
+            Token::Ansi256 { background: false, index: 12 },
+        ]);
+        assert_eq!(tokens(".C208#C236x"), [
+            Token::Ansi256 { background: false, index: 208 },
+            Token::Ansi256 { background: true, index: 236 },
+            Token::Text("x"),
+        ]);
+        assert_eq!(tokens("Saved_X.C1"), [
+            Token::Text("Saved"),
+            Token::Code("\x1b[0m"),
+            Token::Ansi256 { background: false, index: 1 },
+        ]);
+    }
+
+    #[test]
+    fn css_names_start_a_word() {
+        const TAN: Token = Token::Rgb { background: true, rgb: [210, 180, 140] };
+        const RED: Token = Token::Rgb { background: false, rgb: [255, 0, 0] };
+
+        assert_eq!(tokens("report.red.txt"), [Token::Text("report.red.txt")]);
+        assert_eq!(tokens("and #tan and"), [Token::Text("and "), TAN, Token::Text(" and")]);
+        assert_eq!(tokens("x.(red)y"), [Token::Text("x"), RED, Token::Text("y")]);
+        assert_eq!(tokens("x.#ff0000"), [Token::Text("x"), RED]);
+        assert_eq!(tokens(".red#tan"), [RED, TAN]);
+        let colours = tokens("report.red.txt and #tan and file.C12").into_iter();
+        assert_eq!(colours.filter(|t| !matches!(t, Token::Text(_))).collect::<Vec<_>>(), [TAN]);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(tokens("\\_X.RED"), [Token::Text("_X"), Token::Code(crate::codes::RED)]);
+        assert_eq!(tokens("a\\\\_Xb"), [Token::Text("a"), Token::Text("\\"), Token::Code(crate::codes::X), Token::Text("b")]);
+        assert_eq!(tokens("a\\b\\"), [Token::Text("a\\b\\")]);
+    }
+
+    #[test]
+    fn longest_code_wins() {
+        use crate::codes::{BBRRED, BRED, BRRED, RED};
+
+        assert_eq!(tokens(".BRED#BRED#RED.RED"), [
+            Token::Code(BRRED),
+            Token::Code(BBRRED),
+            Token::Code(BRED),
+            Token::Code(RED),
+        ]);
+        assert_eq!(tokens(".C2555"), [Token::Ansi256 { background: false, index: 255 }, Token::Text("5")]);
+    }
+
+    #[test]
+    fn not_colours() {
+        for s in [".C256", ".C", ".(nope)", ".(#ff8800", "#ff8800", ".#ff88", ".orangey", "file.tan2"] {
+            assert_eq!(tokens(s), [Token::Text(s)], "{s:?}");
+        }
+    }
+}